@@ -19,4 +19,45 @@ pub enum TaskerFailure {
 
     #[error("failed to serialize config file: `{0}`")]
     ConfigSerializationError(#[from] toml::ser::Error),
+
+    #[error("no Tasks found with the given ids: {}", join_ids(.0))]
+    TasksNotFound(Vec<usize>),
+
+    #[error("invalid id selector `{0}`: {1}")]
+    InvalidIdSelector(String, &'static str),
+
+    #[error("dependencies would create a cycle: {}", join_cycle(.0))]
+    DependencyCycle(Vec<usize>),
+
+    #[error("subtasks would create a cycle: {}", join_cycle(.0))]
+    SubtaskCycle(Vec<usize>),
+
+    #[error("invalid recurrence `{0}`: {1}")]
+    InvalidRecurrence(String, &'static str),
+
+    #[error("invalid estimate `{0}`: {1}")]
+    InvalidEstimate(String, &'static str),
+
+    #[error("invalid filter `{0}`: {1}")]
+    InvalidFilter(String, &'static str),
+
+    #[error("Task {0} is already being tracked")]
+    AlreadyTracking(usize),
+
+    #[error("no Task is being tracked")]
+    NotTracking,
+}
+
+fn join_ids(ids: &[usize]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn join_cycle(ids: &[usize]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" -> ")
 }
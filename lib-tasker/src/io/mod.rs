@@ -3,6 +3,16 @@ use camino::{Utf8Path, Utf8PathBuf};
 use directories::ProjectDirs;
 use std::io::Write;
 
+/// Name of a Tasks file kept in the directory of a project.
+pub const LOCAL_TO_DO_FILE: &str = ".tasker.ron";
+
+/// Name of a directory kept in the directory of a project, holding its Tasks
+/// file.
+pub const LOCAL_TO_DO_DIRECTORY: &str = ".tasker";
+
+/// Name of the Tasks file in the data directory or in a `.tasker` directory.
+pub const TO_DO_FILE_NAME: &str = "todo.ron";
+
 /// Returns an object containing the project's respective directories to store
 /// data.
 ///
@@ -12,12 +22,12 @@ use std::io::Write;
 /// to store its data in the user's operating system.
 pub fn get_project_directories() -> Result<ProjectDirs, TaskerFailure> {
     let project_directories = ProjectDirs::from("dev", "DaliaReds", "tasker")
-        .ok_or(TaskerFailure::ProjectDirectoryError(
-        std::io::Error::new(
+        .ok_or_else(|| {
+        TaskerFailure::ProjectDirectoryError(std::io::Error::new(
             std::io::ErrorKind::Unsupported,
             "System not supported",
-        ),
-    ))?;
+        ))
+    })?;
 
     if !project_directories.config_dir().exists() {
         std::fs::create_dir_all(project_directories.config_dir())?;
@@ -57,11 +67,34 @@ impl ToDo {
 
         let mut config_dir =
             Utf8PathBuf::try_from(dirs.data_dir().to_path_buf())?;
-        config_dir.push("todo.ron");
+        config_dir.push(TO_DO_FILE_NAME);
 
         Ok(config_dir)
     }
 
+    /// Returns the Tasks file of the closest directory, starting from the
+    /// given one and walking up its parents, that has a `.tasker.ron` file or
+    /// a `.tasker` directory. The file in the latter is `todo.ron`, which
+    /// doesn't have to exist yet.
+    #[must_use]
+    pub fn discover_to_do_path(start: &Utf8Path) -> Option<Utf8PathBuf> {
+        start.ancestors().find_map(|directory| {
+            let file = directory.join(LOCAL_TO_DO_FILE);
+
+            if file.is_file() {
+                return Some(file);
+            }
+
+            let local = directory.join(LOCAL_TO_DO_DIRECTORY);
+
+            if local.is_dir() {
+                Some(local.join(TO_DO_FILE_NAME))
+            } else {
+                None
+            }
+        })
+    }
+
     /// Writes the Tassk into the filesystem.
     ///
     /// # Errors
@@ -1,3 +1,4 @@
 pub mod error;
 pub mod io;
+pub mod statistics;
 pub mod todos;
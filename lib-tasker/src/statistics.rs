@@ -0,0 +1,605 @@
+use crate::todos::{State, Task, ToDo};
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Summary of the Tasks within a `ToDo`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Statistics {
+    pub total: usize,
+    pub states: StateCounts,
+    /// Share of Tasks that are done, from 0 to 1.
+    pub completion_ratio: f64,
+    /// Counts for each project, sorted by name.
+    pub projects: Vec<GroupCount>,
+    /// Counts for each tag, sorted by name.
+    pub tags: Vec<GroupCount>,
+    /// Tasks added and completed during each of the latest weeks, oldest
+    /// first.
+    pub weeks: Vec<WeekActivity>,
+    /// Unfinished Tasks, oldest first. Tasks added before creation times were
+    /// recorded count as the oldest ones.
+    pub oldest_open: Vec<OpenTask>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounts {
+    pub to_do: usize,
+    pub doing: usize,
+    pub waiting: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct GroupCount {
+    pub name: String,
+    pub total: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct WeekActivity {
+    /// Monday the week starts on.
+    pub week: NaiveDate,
+    pub added: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct OpenTask {
+    pub id: usize,
+    pub description: String,
+    pub state: State,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Length of the periods activity is grouped by.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    /// Weeks starting on Monday.
+    Week,
+}
+
+impl Period {
+    /// Returns the first day of the period the given date belongs to.
+    #[must_use]
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => week_start(date),
+        }
+    }
+
+    /// Returns the first day of the period after the one starting on the
+    /// given date.
+    #[must_use]
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        let days = match self {
+            Self::Day => 1,
+            Self::Week => 7,
+        };
+
+        start.checked_add_days(Days::new(days)).unwrap_or(start)
+    }
+
+    /// Returns the first day of every period between the given dates.
+    fn starts(self, since: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let mut starts = Vec::new();
+        let mut start = self.start(since);
+
+        while start <= until {
+            starts.push(start);
+
+            let next = self.next(start);
+            if next == start {
+                break;
+            }
+            start = next;
+        }
+
+        starts
+    }
+}
+
+/// Returns how many Tasks, optionally only those of the given project, were
+/// still open at the end of each day between `since` and `until`.
+///
+/// Tasks without a creation time count as open since forever, and done Tasks
+/// without a completion time as done since forever. Days end at midnight in
+/// the given time zone.
+#[must_use]
+pub fn burndown<Tz: TimeZone>(
+    to_do: &ToDo,
+    project: Option<&str>,
+    since: NaiveDate,
+    until: NaiveDate,
+    timezone: &Tz,
+) -> Vec<(NaiveDate, usize)> {
+    let local_date =
+        |time: DateTime<Utc>| time.with_timezone(timezone).date_naive();
+    let project = project.map(str::to_lowercase);
+    let tasks = to_do
+        .tasks
+        .iter()
+        .filter(|task| {
+            project
+                .as_ref()
+                .map_or(true, |project| &task.project.to_lowercase() == project)
+        })
+        .collect::<Vec<_>>();
+
+    Period::Day
+        .starts(since, until)
+        .into_iter()
+        .map(|day| {
+            let open = tasks
+                .iter()
+                .filter(|task| {
+                    task.created_at
+                        .map_or(true, |created| local_date(created) <= day)
+                })
+                .filter(|task| match (task.state, task.completed_at) {
+                    (State::Done, Some(completed)) => {
+                        local_date(completed) > day
+                    }
+                    (State::Done, None) => false,
+                    _ => true,
+                })
+                .count();
+
+            (day, open)
+        })
+        .collect()
+}
+
+/// Returns how many Tasks were completed during each period between `since`
+/// and `until`, keyed by the first day of the period, dating completions in
+/// the given time zone.
+#[must_use]
+pub fn completions<Tz: TimeZone>(
+    to_do: &ToDo,
+    period: Period,
+    since: NaiveDate,
+    until: NaiveDate,
+    timezone: &Tz,
+) -> Vec<(NaiveDate, usize)> {
+    let mut counts = period
+        .starts(since, until)
+        .into_iter()
+        .map(|start| (start, 0))
+        .collect::<Vec<_>>();
+
+    for completed in to_do
+        .tasks
+        .iter()
+        .filter(|task| task.state == State::Done)
+        .filter_map(|task| task.completed_at)
+        .map(|completed| completed.with_timezone(timezone).date_naive())
+        .filter(|completed| (since..=until).contains(completed))
+    {
+        let start = period.start(completed);
+
+        if let Some((_, count)) =
+            counts.iter_mut().find(|(period, _)| *period == start)
+        {
+            *count += 1;
+        }
+    }
+
+    counts
+}
+
+impl Statistics {
+    /// Computes statistics for the given `ToDo`, with activity for the given
+    /// number of weeks up to the one containing `today`, and at most `oldest`
+    /// open Tasks. Activity is counted by its date in the given time zone.
+    #[must_use]
+    pub fn compute<Tz: TimeZone>(
+        to_do: &ToDo,
+        today: NaiveDate,
+        timezone: &Tz,
+        weeks: usize,
+        oldest: usize,
+    ) -> Self {
+        let mut states = StateCounts::default();
+
+        for task in &to_do.tasks {
+            match task.state {
+                State::ToDo => states.to_do += 1,
+                State::Doing => states.doing += 1,
+                State::Waiting => states.waiting += 1,
+                State::Done => states.done += 1,
+            }
+        }
+
+        let total = to_do.tasks.len();
+
+        #[allow(clippy::cast_precision_loss)]
+        let completion_ratio = if total == 0 {
+            0.0
+        } else {
+            states.done as f64 / total as f64
+        };
+
+        let mut oldest_open = to_do
+            .tasks
+            .iter()
+            .filter(|task| task.state != State::Done)
+            .collect::<Vec<_>>();
+        oldest_open.sort_by_key(|task| (task.created_at, task.id));
+
+        Self {
+            total,
+            states,
+            completion_ratio,
+            projects: group_counts(
+                to_do.tasks.iter().map(|task| (task.project.as_str(), task)),
+            ),
+            tags: group_counts(to_do.tasks.iter().flat_map(|task| {
+                task.tags.iter().map(move |tag| (tag.as_str(), task))
+            })),
+            weeks: weekly_activity(to_do, today, timezone, weeks),
+            oldest_open: oldest_open
+                .into_iter()
+                .take(oldest)
+                .map(|task| OpenTask {
+                    id: task.id,
+                    description: task.description.clone(),
+                    state: task.state,
+                    created_at: task.created_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn group_counts<'a>(
+    tasks: impl Iterator<Item = (&'a str, &'a Task)>,
+) -> Vec<GroupCount> {
+    let mut groups: BTreeMap<&str, GroupCount> = BTreeMap::new();
+
+    for (name, task) in tasks {
+        let group = groups.entry(name).or_insert_with(|| GroupCount {
+            name: name.to_string(),
+            total: 0,
+            done: 0,
+        });
+
+        group.total += 1;
+
+        if task.state == State::Done {
+            group.done += 1;
+        }
+    }
+
+    groups.into_values().collect()
+}
+
+/// Returns the Monday of the week the given date belongs to.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date.checked_sub_days(Days::new(u64::from(
+        date.weekday().num_days_from_monday(),
+    )))
+    .unwrap_or(date)
+}
+
+fn weekly_activity<Tz: TimeZone>(
+    to_do: &ToDo,
+    today: NaiveDate,
+    timezone: &Tz,
+    weeks: usize,
+) -> Vec<WeekActivity> {
+    let current = week_start(today);
+    let mut starts = (0..weeks)
+        .map_while(|weeks_ago| {
+            let days = u64::try_from(weeks_ago).ok()?.checked_mul(7)?;
+            current.checked_sub_days(Days::new(days))
+        })
+        .collect::<Vec<_>>();
+    starts.reverse();
+
+    let index_of = |time: DateTime<Utc>| {
+        let week = week_start(time.with_timezone(timezone).date_naive());
+        starts.iter().position(|&start| start == week)
+    };
+
+    let mut activity = starts
+        .iter()
+        .map(|&week| WeekActivity {
+            week,
+            added: 0,
+            completed: 0,
+        })
+        .collect::<Vec<_>>();
+
+    for task in &to_do.tasks {
+        if let Some(index) = task.created_at.and_then(index_of) {
+            activity[index].added += 1;
+        }
+
+        if let Some(index) = task.completed_at.and_then(index_of) {
+            activity[index].completed += 1;
+        }
+    }
+
+    activity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        format!("{date}T12:00:00Z")
+            .parse()
+            .expect("timestamp should be valid")
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().expect("date should be valid")
+    }
+
+    fn sample_to_do() -> ToDo {
+        let mut done = Task::create("Done")
+            .id(0)
+            .project("Work")
+            .tag("urgent")
+            .state(State::Done)
+            .created_at(at("2024-06-03"))
+            .build();
+        done.completed_at = Some(at("2024-06-11"));
+
+        ToDo::from(vec![
+            done,
+            Task::create("Doing")
+                .id(1)
+                .project("Work")
+                .state(State::Doing)
+                .created_at(at("2024-06-04"))
+                .build(),
+            Task::create("Legacy").id(2).tag("urgent").build(),
+            Task::create("Waiting")
+                .id(3)
+                .state(State::Waiting)
+                .created_at(at("2024-06-12"))
+                .build(),
+        ])
+    }
+
+    #[test]
+    fn counts_states_and_ratio() {
+        let stats = Statistics::compute(
+            &sample_to_do(),
+            date("2024-06-12"),
+            &Utc,
+            2,
+            5,
+        );
+
+        assert_eq!(stats.total, 4);
+        assert_eq!(
+            stats.states,
+            StateCounts {
+                to_do: 1,
+                doing: 1,
+                waiting: 1,
+                done: 1
+            }
+        );
+        assert!((stats.completion_ratio - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn counts_projects_and_tags() {
+        let stats = Statistics::compute(
+            &sample_to_do(),
+            date("2024-06-12"),
+            &Utc,
+            2,
+            5,
+        );
+
+        assert_eq!(
+            stats.projects,
+            vec![
+                GroupCount {
+                    name: "Inbox".to_string(),
+                    total: 2,
+                    done: 0
+                },
+                GroupCount {
+                    name: "Work".to_string(),
+                    total: 2,
+                    done: 1
+                },
+            ]
+        );
+        assert_eq!(
+            stats.tags,
+            vec![GroupCount {
+                name: "urgent".to_string(),
+                total: 2,
+                done: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn counts_weekly_activity() {
+        let stats = Statistics::compute(
+            &sample_to_do(),
+            date("2024-06-12"),
+            &Utc,
+            3,
+            5,
+        );
+
+        assert_eq!(
+            stats.weeks,
+            vec![
+                WeekActivity {
+                    week: date("2024-05-27"),
+                    added: 0,
+                    completed: 0
+                },
+                WeekActivity {
+                    week: date("2024-06-03"),
+                    added: 2,
+                    completed: 0
+                },
+                WeekActivity {
+                    week: date("2024-06-10"),
+                    added: 1,
+                    completed: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_weekly_activity_in_the_given_time_zone() {
+        let late_sunday = "2024-06-09T20:00:00Z"
+            .parse()
+            .expect("timestamp should be valid");
+        let to_do = ToDo::from(vec![Task::create("Late")
+            .id(0)
+            .created_at(late_sunday)
+            .build()]);
+        let added = |timezone: &FixedOffset| {
+            Statistics::compute(&to_do, date("2024-06-12"), timezone, 2, 5)
+                .weeks
+                .iter()
+                .map(|week| week.added)
+                .collect::<Vec<_>>()
+        };
+
+        let utc = FixedOffset::east_opt(0).expect("offset should be valid");
+        let auckland =
+            FixedOffset::east_opt(12 * 3600).expect("offset should be valid");
+
+        assert_eq!(added(&utc), vec![1, 0]);
+        assert_eq!(added(&auckland), vec![0, 1]);
+    }
+
+    #[test]
+    fn computes_burndown() {
+        assert_eq!(
+            burndown(
+                &sample_to_do(),
+                Some("work"),
+                date("2024-06-02"),
+                date("2024-06-05"),
+                &Utc
+            ),
+            vec![
+                (date("2024-06-02"), 0),
+                (date("2024-06-03"), 1),
+                (date("2024-06-04"), 2),
+                (date("2024-06-05"), 2),
+            ]
+        );
+        assert_eq!(
+            burndown(
+                &sample_to_do(),
+                None,
+                date("2024-06-11"),
+                date("2024-06-12"),
+                &Utc
+            ),
+            vec![(date("2024-06-11"), 2), (date("2024-06-12"), 3)]
+        );
+    }
+
+    #[test]
+    fn dates_completions_in_the_given_time_zone() {
+        let mut late = Task::create("Late").id(0).state(State::Done).build();
+        late.completed_at = Some(
+            "2024-06-11T20:00:00Z"
+                .parse()
+                .expect("timestamp should be valid"),
+        );
+        let to_do = ToDo::from(vec![late]);
+        let auckland =
+            FixedOffset::east_opt(12 * 3600).expect("offset should be valid");
+
+        assert_eq!(
+            completions(
+                &to_do,
+                Period::Day,
+                date("2024-06-11"),
+                date("2024-06-12"),
+                &auckland
+            ),
+            vec![(date("2024-06-11"), 0), (date("2024-06-12"), 1)]
+        );
+        assert_eq!(
+            burndown(
+                &to_do,
+                None,
+                date("2024-06-11"),
+                date("2024-06-12"),
+                &auckland
+            ),
+            vec![(date("2024-06-11"), 1), (date("2024-06-12"), 0)]
+        );
+    }
+
+    #[test]
+    fn counts_completions_per_period() {
+        let mut todo = sample_to_do();
+        let mut late = Task::create("Late").id(4).state(State::Done).build();
+        late.completed_at = Some(at("2024-06-13"));
+        todo.add_task(late);
+
+        assert_eq!(
+            completions(
+                &todo,
+                Period::Day,
+                date("2024-06-11"),
+                date("2024-06-13"),
+                &Utc
+            ),
+            vec![
+                (date("2024-06-11"), 1),
+                (date("2024-06-12"), 0),
+                (date("2024-06-13"), 1),
+            ]
+        );
+        assert_eq!(
+            completions(
+                &todo,
+                Period::Week,
+                date("2024-06-01"),
+                date("2024-06-13"),
+                &Utc
+            ),
+            vec![
+                (date("2024-05-27"), 0),
+                (date("2024-06-03"), 0),
+                (date("2024-06-10"), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_oldest_open_tasks() {
+        let stats = Statistics::compute(
+            &sample_to_do(),
+            date("2024-06-12"),
+            &Utc,
+            2,
+            2,
+        );
+
+        assert_eq!(
+            stats
+                .oldest_open
+                .iter()
+                .map(|task| task.id)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::TaskerFailure;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, ops::AddAssign, str::FromStr};
+
+/// How much effort a Task is expected to take.
+#[derive(
+    Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, PartialOrd, Ord,
+)]
+pub enum Estimate {
+    /// A length of time in minutes, e.g. `1h30m`.
+    Minutes(u32),
+    /// An amount of story points, e.g. `3pts`.
+    Points(u32),
+}
+
+/// Sum of several estimates, keeping time and points apart since they can't
+/// be converted into each other.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Effort {
+    pub minutes: u32,
+    pub points: u32,
+}
+
+impl Effort {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.minutes == 0 && self.points == 0
+    }
+}
+
+impl AddAssign<Estimate> for Effort {
+    fn add_assign(&mut self, estimate: Estimate) {
+        match estimate {
+            Estimate::Minutes(minutes) => {
+                self.minutes = self.minutes.saturating_add(minutes);
+            }
+            Estimate::Points(points) => {
+                self.points = self.points.saturating_add(points);
+            }
+        }
+    }
+}
+
+impl FromStr for Estimate {
+    type Err = TaskerFailure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let estimate: String = s
+            .chars()
+            .filter(|character| !character.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        let invalid =
+            |reason| TaskerFailure::InvalidEstimate(s.to_string(), reason);
+
+        for suffix in ["points", "pts", "pt", "p"] {
+            if let Some(points) = estimate.strip_suffix(suffix) {
+                return match points.parse() {
+                    Ok(points @ 1..) => Ok(Self::Points(points)),
+                    _ => {
+                        Err(invalid("expected a number of points like `3pts`"))
+                    }
+                };
+            }
+        }
+
+        let mut minutes: u32 = 0;
+        let mut number = String::new();
+        let mut units = Vec::new();
+
+        for character in estimate.chars() {
+            if character.is_ascii_digit() {
+                number.push(character);
+                continue;
+            }
+
+            let factor = match character {
+                'h' => 60,
+                'm' => 1,
+                _ => return Err(invalid("expected a duration like `1h30m`")),
+            };
+
+            if number.is_empty() || units.contains(&character) {
+                return Err(invalid("expected a duration like `1h30m`"));
+            }
+
+            units.push(character);
+            minutes = number
+                .parse::<u32>()
+                .ok()
+                .and_then(|value| value.checked_mul(factor))
+                .and_then(|value| minutes.checked_add(value))
+                .ok_or_else(|| invalid("the duration is too long"))?;
+            number.clear();
+        }
+
+        if !number.is_empty() || minutes == 0 {
+            return Err(invalid(
+                "expected a duration like `1h30m` or points like `3pts`",
+            ));
+        }
+
+        Ok(Self::Minutes(minutes))
+    }
+}
+
+/// Writes minutes as hours and minutes, omitting whichever is zero.
+fn write_minutes(
+    f: &mut std::fmt::Formatter<'_>,
+    minutes: u32,
+) -> std::fmt::Result {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => write!(f, "{minutes}m"),
+        (hours, 0) => write!(f, "{hours}h"),
+        (hours, minutes) => write!(f, "{hours}h{minutes}m"),
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Minutes(minutes) => write_minutes(f, *minutes),
+            Self::Points(points) => write!(f, "{points}pts"),
+        }
+    }
+}
+
+impl Display for Effort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.minutes, self.points) {
+            (0, 0) => write!(f, "0m"),
+            (minutes, 0) => write_minutes(f, minutes),
+            (0, points) => write!(f, "{points}pts"),
+            (minutes, points) => {
+                write_minutes(f, minutes)?;
+                write!(f, " + {points}pts")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_estimates() {
+        assert_eq!(
+            "1h30m".parse::<Estimate>().ok(),
+            Some(Estimate::Minutes(90))
+        );
+        assert_eq!(
+            "2H 15m".parse::<Estimate>().ok(),
+            Some(Estimate::Minutes(135))
+        );
+        assert_eq!("45m".parse::<Estimate>().ok(), Some(Estimate::Minutes(45)));
+        assert_eq!("3pts".parse::<Estimate>().ok(), Some(Estimate::Points(3)));
+        assert_eq!("5 p".parse::<Estimate>().ok(), Some(Estimate::Points(5)));
+    }
+
+    #[test]
+    fn rejects_invalid_estimates() {
+        assert!("3".parse::<Estimate>().is_err());
+        assert!("0h".parse::<Estimate>().is_err());
+        assert!("1h1h".parse::<Estimate>().is_err());
+        assert!("2d".parse::<Estimate>().is_err());
+        assert!("0pts".parse::<Estimate>().is_err());
+        assert!("h".parse::<Estimate>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for estimate in ["1h30m", "2h", "45m", "8pts"] {
+            assert_eq!(
+                estimate
+                    .parse::<Estimate>()
+                    .map(|estimate| estimate.to_string())
+                    .ok(),
+                Some(estimate.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn effort_adds_estimates() {
+        let mut effort = Effort::default();
+        effort += Estimate::Minutes(90);
+        effort += Estimate::Minutes(30);
+        effort += Estimate::Points(3);
+
+        assert_eq!(
+            effort,
+            Effort {
+                minutes: 120,
+                points: 3
+            }
+        );
+        assert_eq!(effort.to_string(), "2h + 3pts");
+    }
+}
@@ -0,0 +1,483 @@
+use super::{IdSelection, IdSelector, State, Task, ToDo};
+use crate::error::TaskerFailure;
+use chrono::NaiveDate;
+use std::str::FromStr;
+use str_slug::slug;
+
+/// A condition Tasks can be matched against, parsed from expressions like
+/// `project:work and (+urgent or due:overdue) not state:done`.
+///
+/// Terms next to each other must all match, as if joined by `and`. The
+/// supported terms are:
+///
+/// - `project:NAME`, `state:STATE` and `tag:NAME` or `+NAME`.
+/// - `-NAME`, for Tasks without the given tag.
+/// - `id:IDS`, accepting ranges and lists like `3-8` or `1,4,9-12`.
+/// - `due:DATE`, for Tasks due on or before it, as well as `due:today`,
+///   `due:overdue`, `due:any` and `due:none`.
+/// - `estimate:any` and `estimate:none`.
+/// - `is:blocked`, `is:unblocked`, `is:open` and `is:tracking`.
+/// - Any other word, for Tasks containing it within their descriptions.
+///   Quotes allow searching for several words at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Matches every Task, as the empty expression does.
+    Any,
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+    Not(Box<Self>),
+    Description(String),
+    /// Name of a project as written, matched regardless of case.
+    Project(String),
+    State(State),
+    Tag(String),
+    Ids(IdSelection),
+    Due(DueFilter),
+    Estimated(bool),
+    Blocked(bool),
+    Open,
+    Tracking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueFilter {
+    None,
+    Any,
+    Today,
+    Overdue,
+    OnOrBefore(NaiveDate),
+}
+
+impl Filter {
+    /// Whether the given Task of the given `ToDo` matches this filter, taking
+    /// `today` as the reference for due dates.
+    #[must_use]
+    pub fn matches(&self, task: &Task, to_do: &ToDo, today: NaiveDate) -> bool {
+        match self {
+            Self::Any => true,
+            Self::And(left, right) => {
+                left.matches(task, to_do, today)
+                    && right.matches(task, to_do, today)
+            }
+            Self::Or(left, right) => {
+                left.matches(task, to_do, today)
+                    || right.matches(task, to_do, today)
+            }
+            Self::Not(filter) => !filter.matches(task, to_do, today),
+            Self::Description(text) => {
+                task.description.to_lowercase().contains(text)
+            }
+            Self::Project(project) => {
+                task.project.to_lowercase() == project.to_lowercase()
+            }
+            Self::State(state) => task.state == *state,
+            Self::Tag(tag) => task.tags.contains(tag),
+            Self::Ids(selection) => {
+                selection.0.iter().any(|selector| match *selector {
+                    IdSelector::Id(id) => id == task.id,
+                    IdSelector::Range(start, end) => {
+                        (start..=end).contains(&task.id)
+                    }
+                    IdSelector::Last => {
+                        to_do.tasks.iter().map(|task| task.id).max()
+                            == Some(task.id)
+                    }
+                    IdSelector::All => true,
+                })
+            }
+            Self::Due(due) => match (due, task.due) {
+                (DueFilter::None, due) => due.is_none(),
+                (_, None) => false,
+                (DueFilter::Any, Some(_)) => true,
+                (DueFilter::Today, Some(due)) => due == today,
+                (DueFilter::Overdue, Some(due)) => {
+                    due < today && task.state != State::Done
+                }
+                (DueFilter::OnOrBefore(date), Some(due)) => due <= *date,
+            },
+            Self::Estimated(estimated) => task.estimate.is_some() == *estimated,
+            Self::Blocked(blocked) => to_do.is_blocked(task) == *blocked,
+            Self::Open => task.state != State::Done,
+            Self::Tracking => task.is_tracking(),
+        }
+    }
+
+    /// Returns a filter matching Tasks that match both this one and the given
+    /// one.
+    #[must_use]
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Any, filter) | (filter, Self::Any) => filter,
+            (left, right) => Self::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Returns the project a new Task should have to match this filter: the
+    /// first one named by a term that isn't negated, if any.
+    #[must_use]
+    pub fn implied_project(&self) -> Option<&str> {
+        let mut projects = Vec::new();
+        self.collect_implied(&mut projects, &mut Vec::new());
+
+        projects.first().copied()
+    }
+
+    /// Returns the tags a new Task should have to match this filter: those
+    /// of every tag term that isn't negated.
+    #[must_use]
+    pub fn implied_tags(&self) -> Vec<&str> {
+        let mut tags = Vec::new();
+        self.collect_implied(&mut Vec::new(), &mut tags);
+
+        tags
+    }
+
+    /// Collects the projects and tags of the terms that aren't negated. Both
+    /// sides of an `or` are collected, since a Task with all of them matches
+    /// either.
+    fn collect_implied<'a>(
+        &'a self,
+        projects: &mut Vec<&'a str>,
+        tags: &mut Vec<&'a str>,
+    ) {
+        match self {
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.collect_implied(projects, tags);
+                right.collect_implied(projects, tags);
+            }
+            Self::Project(project) => projects.push(project),
+            Self::Tag(tag) if !tags.contains(&tag.as_str()) => tags.push(tag),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut characters = expression.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '(' | ')' | ' ' | '\t' | '\n' => {
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                }
+
+                match character {
+                    '(' => tokens.push(Token::Open),
+                    ')' => tokens.push(Token::Close),
+                    _ => {}
+                }
+            }
+            '"' => loop {
+                match characters.next() {
+                    Some('"') => break,
+                    Some(character) => word.push(character),
+                    None => return Err("unclosed quote"),
+                }
+            },
+            _ => word.push(character),
+        }
+    }
+
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, &'static str> {
+        let mut filter = self.parse_and()?;
+
+        while self.peek_keyword("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, &'static str> {
+        let mut filter = self.parse_unary()?;
+
+        loop {
+            if self.peek_keyword("and") {
+                self.position += 1;
+            } else if self.peek_keyword("or")
+                || matches!(self.peek(), None | Some(Token::Close))
+            {
+                return Ok(filter);
+            }
+
+            filter =
+                Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, &'static str> {
+        match self.next() {
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("missing `)`"),
+                }
+            }
+            Some(Token::Close) => Err("unexpected `)`"),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("not") => {
+                Ok(Filter::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Word(word))
+                if word.eq_ignore_ascii_case("and")
+                    || word.eq_ignore_ascii_case("or") =>
+            {
+                Err("expected a term before and after `and` and `or`")
+            }
+            Some(Token::Word(word)) => parse_term(&word),
+            None => Err("expected a term at the end"),
+        }
+    }
+}
+
+fn parse_term(term: &str) -> Result<Filter, &'static str> {
+    if let Some(tag) = term.strip_prefix('+') {
+        return Ok(Filter::Tag(slug(tag)));
+    }
+
+    if let Some(tag) = term.strip_prefix('-') {
+        return Ok(Filter::Not(Box::new(Filter::Tag(slug(tag)))));
+    }
+
+    let Some((key, value)) = term.split_once(':') else {
+        return Ok(Filter::Description(term.to_lowercase()));
+    };
+
+    if matches!(key.to_lowercase().as_str(), "project" | "pro") {
+        return Ok(Filter::Project(value.trim().to_string()));
+    }
+
+    let value = value.trim().to_lowercase();
+
+    match key.to_lowercase().as_str() {
+        "tag" => Ok(Filter::Tag(slug(value))),
+        "description" | "desc" => Ok(Filter::Description(value)),
+        "state" => match value.as_str() {
+            "todo" | "to-do" => Ok(Filter::State(State::ToDo)),
+            "doing" => Ok(Filter::State(State::Doing)),
+            "waiting" => Ok(Filter::State(State::Waiting)),
+            "done" => Ok(Filter::State(State::Done)),
+            _ => Err(
+                "expected a state like `todo`, `doing`, `waiting` or `done`",
+            ),
+        },
+        "id" | "ids" => value
+            .parse()
+            .map(Filter::Ids)
+            .map_err(|_| "expected ids like `3-8` or `1,4,9-12`"),
+        "due" => match value.as_str() {
+            "none" => Ok(Filter::Due(DueFilter::None)),
+            "any" => Ok(Filter::Due(DueFilter::Any)),
+            "today" => Ok(Filter::Due(DueFilter::Today)),
+            "overdue" => Ok(Filter::Due(DueFilter::Overdue)),
+            date => date
+                .parse()
+                .map(|date| Filter::Due(DueFilter::OnOrBefore(date)))
+                .map_err(|_| {
+                    "expected `today`, `overdue`, `any`, `none` or a date"
+                }),
+        },
+        "estimate" => match value.as_str() {
+            "any" => Ok(Filter::Estimated(true)),
+            "none" => Ok(Filter::Estimated(false)),
+            _ => Err("expected `estimate:any` or `estimate:none`"),
+        },
+        "is" => match value.as_str() {
+            "blocked" => Ok(Filter::Blocked(true)),
+            "unblocked" => Ok(Filter::Blocked(false)),
+            "open" => Ok(Filter::Open),
+            "tracking" => Ok(Filter::Tracking),
+            _ => Err("expected `blocked`, `unblocked`, `open` or `tracking`"),
+        },
+        _ => Err("unknown key, expected `project`, `state`, `tag`, `id`, \
+                  `description`, `due`, `estimate` or `is`"),
+    }
+}
+
+impl FromStr for Filter {
+    type Err = TaskerFailure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason| TaskerFailure::InvalidFilter(s.to_string(), reason);
+
+        let mut parser = Parser {
+            tokens: tokenize(s).map_err(invalid)?,
+            position: 0,
+        };
+
+        if parser.tokens.is_empty() {
+            return Ok(Self::Any);
+        }
+
+        let filter = parser.parse_or().map_err(invalid)?;
+
+        match parser.peek() {
+            None => Ok(filter),
+            Some(_) => Err(invalid("unexpected `)`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().expect("date should be valid")
+    }
+
+    fn sample_to_do() -> ToDo {
+        ToDo::from(vec![
+            Task::create("Write the report")
+                .id(0)
+                .project("Work")
+                .tag("urgent")
+                .due(date("2024-06-01"))
+                .build(),
+            Task::create("Buy groceries")
+                .id(1)
+                .project("Home")
+                .state(State::Done)
+                .build(),
+            Task::create("Review the report")
+                .id(2)
+                .project("Work")
+                .depends_on([0])
+                .build(),
+        ])
+    }
+
+    fn matching(expression: &str) -> Vec<usize> {
+        let todo = sample_to_do();
+        let filter = expression
+            .parse::<Filter>()
+            .expect("filter should be valid");
+
+        todo.tasks
+            .iter()
+            .filter(|task| filter.matches(task, &todo, date("2024-06-05")))
+            .map(|task| task.id)
+            .collect()
+    }
+
+    #[test]
+    fn parses_precedence() {
+        assert_eq!(
+            "project:work +urgent or not state:done"
+                .parse::<Filter>()
+                .ok(),
+            Some(Filter::Or(
+                Box::new(Filter::And(
+                    Box::new(Filter::Project("work".to_string())),
+                    Box::new(Filter::Tag("urgent".to_string()))
+                )),
+                Box::new(Filter::Not(Box::new(Filter::State(State::Done))))
+            ))
+        );
+    }
+
+    #[test]
+    fn matches_terms() {
+        assert_eq!(matching(""), vec![0, 1, 2]);
+        assert_eq!(matching("project:work"), vec![0, 2]);
+        assert_eq!(matching("+urgent"), vec![0]);
+        assert_eq!(matching("-urgent"), vec![1, 2]);
+        assert_eq!(matching("state:done"), vec![1]);
+        assert_eq!(matching("id:1-2"), vec![1, 2]);
+        assert_eq!(matching("id:last"), vec![2]);
+        assert_eq!(matching("due:overdue"), vec![0]);
+        assert_eq!(matching("due:none"), vec![1, 2]);
+        assert_eq!(matching("is:blocked"), vec![2]);
+        assert_eq!(matching("\"the report\""), vec![0, 2]);
+    }
+
+    #[test]
+    fn matches_combinations() {
+        assert_eq!(matching("project:work and not +urgent"), vec![2]);
+        assert_eq!(matching("(project:home or +urgent) is:open"), vec![0]);
+        assert_eq!(matching("report not (review or +urgent)"), Vec::new());
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        for expression in [
+            "(project:work",
+            "project:work)",
+            "state:someday",
+            "colour:red",
+            "+urgent or",
+            "and +urgent",
+            "\"unclosed",
+        ] {
+            assert!(expression.parse::<Filter>().is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn implies_project_and_tags() {
+        let filter = "project:Work +urgent or (tag:office -remote)"
+            .parse::<Filter>()
+            .expect("filter should be valid");
+
+        assert_eq!(filter.implied_project(), Some("Work"));
+        assert_eq!(filter.implied_tags(), vec!["urgent", "office"]);
+
+        let filter = "not project:Home state:todo"
+            .parse::<Filter>()
+            .expect("filter should be valid");
+
+        assert_eq!(filter.implied_project(), None);
+        assert!(filter.implied_tags().is_empty());
+    }
+
+    #[test]
+    fn and_skips_any() {
+        let filter = Filter::Tag("urgent".to_string());
+
+        assert_eq!(Filter::Any.and(filter.clone()), filter);
+        assert_eq!(filter.clone().and(Filter::Any), filter);
+    }
+}
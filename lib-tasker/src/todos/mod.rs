@@ -1,6 +1,18 @@
+pub mod estimate;
+pub mod filter;
+pub mod recurrence;
+pub mod selectors;
 pub mod tasks;
 
+use crate::error::TaskerFailure;
+use chrono::{DateTime, NaiveDate, Utc};
+pub use estimate::*;
+pub use filter::*;
+use indexmap::IndexSet;
+pub use recurrence::*;
+pub use selectors::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 pub use tasks::*;
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
@@ -14,10 +26,560 @@ impl From<Vec<Task>> for ToDo {
     }
 }
 
+/// Result of looking up a list of ids within a `ToDo`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdMatches {
+    /// Ids that belong to an existing Task, in the order they were given.
+    pub found: Vec<usize>,
+    /// Ids that don't belong to any Task, in the order they were given.
+    pub missing: Vec<usize>,
+}
+
+/// Result of moving Tasks within a `ToDo` to another state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transition {
+    /// Ids that were looked up to change their state.
+    pub matches: IdMatches,
+    /// Ids of the Tasks that no longer wait on an unfinished one.
+    pub unblocked: Vec<usize>,
+    /// Ids of the occurrences added for completed recurring Tasks.
+    pub recurrences: Vec<usize>,
+}
+
 impl ToDo {
     pub fn add_task(&mut self, task: Task) {
         self.tasks.push(task);
     }
+
+    /// Returns the id the next added Task should have.
+    #[must_use]
+    pub fn next_id(&self) -> usize {
+        self.tasks.iter().map(|task| task.id + 1).max().unwrap_or(0)
+    }
+
+    /// Returns the Task with the given id, if any.
+    #[must_use]
+    pub fn get_task(&self, id: usize) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    /// Expands the given selections into the ids they refer to, in the order
+    /// they were given and without duplicates. Single ids that don't belong to
+    /// any Task are kept so they can be reported, while ranges only cover the
+    /// Tasks within them, however wide they are.
+    #[must_use]
+    pub fn resolve_ids(&self, selections: &[IdSelection]) -> Vec<usize> {
+        let mut ids = Vec::new();
+
+        for selector in selections.iter().flat_map(|selection| &selection.0) {
+            match *selector {
+                IdSelector::Id(id) => ids.push(id),
+                IdSelector::Range(start, end) => {
+                    let mut within: Vec<usize> = self
+                        .tasks
+                        .iter()
+                        .map(|task| task.id)
+                        .filter(|id| (start..=end).contains(id))
+                        .collect();
+                    within.sort_unstable();
+
+                    ids.extend(within);
+                }
+                IdSelector::Last => {
+                    ids.extend(self.tasks.iter().map(|task| task.id).max());
+                }
+                IdSelector::All => {
+                    ids.extend(self.tasks.iter().map(|task| task.id));
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+
+        ids
+    }
+
+    /// Splits the given ids between the ones that belong to a Task and the
+    /// ones that don't. Duplicated ids are only reported once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every given id if none of them belong to a
+    /// Task.
+    pub fn match_ids(&self, ids: &[usize]) -> Result<IdMatches, TaskerFailure> {
+        let mut matches = IdMatches::default();
+
+        for &id in ids {
+            if matches.found.contains(&id) || matches.missing.contains(&id) {
+                continue;
+            }
+
+            if self.tasks.iter().any(|task| task.id == id) {
+                matches.found.push(id);
+            } else {
+                matches.missing.push(id);
+            }
+        }
+
+        if matches.found.is_empty() {
+            return Err(TaskerFailure::TasksNotFound(matches.missing));
+        }
+
+        Ok(matches)
+    }
+
+    /// Deletes the Tasks with the given ids. Subtasks of a deleted Task are
+    /// moved up to its closest ancestor that isn't deleted, and other Tasks
+    /// stop depending on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if none of the given ids belong to a Task, in which
+    /// case nothing is deleted.
+    pub fn delete_tasks(
+        &mut self,
+        ids: &[usize],
+    ) -> Result<IdMatches, TaskerFailure> {
+        let matches = self.match_ids(ids)?;
+
+        for &id in &matches.found {
+            let mut grandparent =
+                self.get_task(id).and_then(|task| task.parent);
+
+            while let Some(parent) =
+                grandparent.filter(|parent| matches.found.contains(parent))
+            {
+                grandparent =
+                    self.get_task(parent).and_then(|task| task.parent);
+            }
+
+            self.tasks
+                .iter_mut()
+                .filter(|task| task.parent == Some(id))
+                .for_each(|task| task.parent = grandparent);
+        }
+
+        self.tasks.retain(|task| !matches.found.contains(&task.id));
+
+        for task in &mut self.tasks {
+            task.depends_on.retain(|id| !matches.found.contains(id));
+        }
+
+        Ok(matches)
+    }
+
+    /// Deletes every completed Task the same way as `delete_tasks`, and
+    /// returns their ids.
+    pub fn clean_completed(&mut self) -> Vec<usize> {
+        let done: Vec<usize> = self
+            .tasks
+            .iter()
+            .filter(|task| task.state == State::Done)
+            .map(|task| task.id)
+            .collect();
+
+        if done.is_empty() {
+            return done;
+        }
+
+        self.delete_tasks(&done)
+            .map(|matches| matches.found)
+            .unwrap_or_default()
+    }
+
+    /// Returns the direct subtasks of the Task with the given id.
+    pub fn children(&self, id: usize) -> impl Iterator<Item = &Task> {
+        self.tasks
+            .iter()
+            .filter(move |task| task.parent == Some(id))
+    }
+
+    /// Returns the total estimated effort of the unfinished and the finished
+    /// Tasks of the given project, in that order.
+    #[must_use]
+    pub fn project_effort(&self, project: &str) -> (Effort, Effort) {
+        let mut remaining = Effort::default();
+        let mut completed = Effort::default();
+
+        for task in self.tasks.iter().filter(|task| task.project == project) {
+            if let Some(estimate) = task.estimate {
+                if task.state == State::Done {
+                    completed += estimate;
+                } else {
+                    remaining += estimate;
+                }
+            }
+        }
+
+        (remaining, completed)
+    }
+
+    /// Returns how many direct subtasks of the given Task are done, and how
+    /// many subtasks it has in total, or `None` if it has no subtasks.
+    #[must_use]
+    pub fn subtask_progress(&self, id: usize) -> Option<(usize, usize)> {
+        let (done, total) =
+            self.children(id).fold((0, 0), |(done, total), task| {
+                (done + usize::from(task.state == State::Done), total + 1)
+            });
+
+        (total > 0).then_some((done, total))
+    }
+
+    /// Returns the ids of every subtask, at any depth, of the given Tasks that
+    /// isn't done yet. Tasks within `ids` are never included.
+    #[must_use]
+    pub fn open_descendants(&self, ids: &[usize]) -> Vec<usize> {
+        let mut pending = ids.to_vec();
+        let mut visited: Vec<usize> = ids.to_vec();
+        let mut open = Vec::new();
+
+        while let Some(id) = pending.pop() {
+            for child in self.children(id) {
+                if visited.contains(&child.id) {
+                    continue;
+                }
+
+                visited.push(child.id);
+                pending.push(child.id);
+
+                if child.state != State::Done {
+                    open.push(child.id);
+                }
+            }
+        }
+
+        open.sort_unstable();
+        open
+    }
+
+    /// Makes the Task with the given id a subtask of another one, or a top
+    /// level Task if `parent` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either Task doesn't exist, or if the Task would end
+    /// up being its own ancestor. Nothing changes in either case.
+    pub fn set_parent(
+        &mut self,
+        id: usize,
+        parent: Option<usize>,
+    ) -> Result<(), TaskerFailure> {
+        let missing: Vec<usize> = std::iter::once(id)
+            .chain(parent)
+            .filter(|&id| self.get_task(id).is_none())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(TaskerFailure::TasksNotFound(missing));
+        }
+
+        let mut ancestors = vec![id];
+        let mut current = parent;
+
+        while let Some(ancestor) = current {
+            ancestors.push(ancestor);
+
+            if ancestor == id {
+                return Err(TaskerFailure::SubtaskCycle(ancestors));
+            }
+
+            current = self.get_task(ancestor).and_then(|task| task.parent);
+
+            if ancestors.len() > self.tasks.len() + 1 {
+                break;
+            }
+        }
+
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.parent = parent;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the dependencies of the Task with the given id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Task or any of its dependencies don't exist, or
+    /// if the dependencies would create a cycle. Nothing changes in either
+    /// case.
+    pub fn set_dependencies(
+        &mut self,
+        id: usize,
+        dependencies: impl IntoIterator<Item = usize>,
+    ) -> Result<(), TaskerFailure> {
+        let dependencies: IndexSet<usize> = dependencies.into_iter().collect();
+
+        let missing: Vec<usize> = std::iter::once(id)
+            .chain(dependencies.iter().copied())
+            .filter(|&id| self.get_task(id).is_none())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(TaskerFailure::TasksNotFound(missing));
+        }
+
+        for &dependency in &dependencies {
+            if let Some(mut cycle) = self.dependency_path(dependency, id) {
+                cycle.insert(0, id);
+                return Err(TaskerFailure::DependencyCycle(cycle));
+            }
+        }
+
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.depends_on = dependencies;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the chain of dependencies leading from one Task to another,
+    /// both included, if there is one.
+    fn dependency_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut pending = vec![vec![from]];
+        let mut visited = HashSet::new();
+
+        while let Some(path) = pending.pop() {
+            let current = *path.last()?;
+
+            if current == to {
+                return Some(path);
+            }
+
+            if !visited.insert(current) {
+                continue;
+            }
+
+            if let Some(task) = self.get_task(current) {
+                for &dependency in &task.depends_on {
+                    let mut next = path.clone();
+                    next.push(dependency);
+                    pending.push(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the ids of the dependencies of the given Task that aren't done
+    /// yet.
+    #[must_use]
+    pub fn blockers(&self, task: &Task) -> Vec<usize> {
+        task.depends_on
+            .iter()
+            .copied()
+            .filter(|&id| {
+                self.get_task(id)
+                    .map_or(false, |dependency| dependency.state != State::Done)
+            })
+            .collect()
+    }
+
+    /// Whether the given Task depends on a Task that isn't done yet.
+    #[must_use]
+    pub fn is_blocked(&self, task: &Task) -> bool {
+        !self.blockers(task).is_empty()
+    }
+
+    /// Returns the ids of the unfinished Tasks that depend on any of the given
+    /// ones and aren't blocked anymore.
+    #[must_use]
+    pub fn unblocked_by(&self, ids: &[usize]) -> Vec<usize> {
+        self.tasks
+            .iter()
+            .filter(|task| {
+                task.state != State::Done
+                    && task.depends_on.iter().any(|id| ids.contains(id))
+                    && !self.is_blocked(task)
+            })
+            .map(|task| task.id)
+            .collect()
+    }
+
+    /// Creates the next occurrence of every completed recurring Task among the
+    /// given ids, and returns the ids of the new Tasks. The recurrence moves
+    /// to the new occurrence, so completing a Task twice doesn't repeat it
+    /// twice.
+    pub fn spawn_recurrences(
+        &mut self,
+        ids: &[usize],
+        today: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> Vec<usize> {
+        let mut spawned = Vec::new();
+
+        for index in 0..self.tasks.len() {
+            let task = &mut self.tasks[index];
+
+            if task.state != State::Done || !ids.contains(&task.id) {
+                continue;
+            }
+
+            let Some(recurrence) = task.recurrence.take() else {
+                continue;
+            };
+
+            let due =
+                recurrence.next_occurrence(task.due.unwrap_or(today), today);
+
+            let mut next = Task::create(task.description.clone());
+            next.project(task.project.clone())
+                .tags(task.tags.clone())
+                .due(due)
+                .recurrence(recurrence)
+                .created_at(now);
+
+            if let Some(parent) = task.parent {
+                next.parent(parent);
+            }
+
+            let id = self.next_id();
+            self.add_task(next.id(id).build());
+            spawned.push(id);
+        }
+
+        spawned
+    }
+
+    /// Records the given time as the completion time of the Tasks with the
+    /// given ids that are done and don't have one yet, and clears it from the
+    /// ones that aren't done.
+    pub fn record_completions(&mut self, ids: &[usize], now: DateTime<Utc>) {
+        for task in self.tasks.iter_mut().filter(|task| ids.contains(&task.id))
+        {
+            if task.state != State::Done {
+                task.completed_at = None;
+            } else if task.completed_at.is_none() {
+                task.completed_at = Some(now);
+            }
+        }
+    }
+
+    /// Returns the Task whose time is currently being tracked, if any.
+    #[must_use]
+    pub fn tracked_task(&self) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.is_tracking())
+    }
+
+    /// Starts tracking time for the Task with the given id and moves it to
+    /// `Doing` along with everything that follows from it, like
+    /// [`ToDo::transition`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Task doesn't exist, or if any Task is already
+    /// being tracked, since only one can be at a time.
+    pub fn start_tracking(
+        &mut self,
+        id: usize,
+        today: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> Result<Transition, TaskerFailure> {
+        if let Some(tracked) = self.tracked_task() {
+            return Err(TaskerFailure::AlreadyTracking(tracked.id));
+        }
+
+        let transition = self.transition(&[id], State::Doing, today, now)?;
+
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.time_log.push(TimeInterval {
+                start: now,
+                end: None,
+            });
+        }
+
+        Ok(transition)
+    }
+
+    /// Stops tracking time for the Task currently being tracked, and returns
+    /// its id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no Task is being tracked.
+    pub fn stop_tracking(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<usize, TaskerFailure> {
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.is_tracking())
+            .ok_or(TaskerFailure::NotTracking)?;
+
+        if let Some(interval) = task.time_log.last_mut() {
+            interval.end = Some(now.max(interval.start));
+        }
+
+        Ok(task.id)
+    }
+
+    /// Changes the state of the Tasks with the given ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if none of the given ids belong to a Task, in which
+    /// case no Task is changed.
+    pub fn change_state(
+        &mut self,
+        ids: &[usize],
+        state: State,
+    ) -> Result<IdMatches, TaskerFailure> {
+        let matches = self.match_ids(ids)?;
+
+        self.tasks
+            .iter_mut()
+            .filter(|task| matches.found.contains(&task.id))
+            .for_each(|task| task.change_state(state));
+
+        Ok(matches)
+    }
+
+    /// Moves the Tasks with the given ids to another state along with
+    /// everything that follows from it: stops tracking time for them unless
+    /// they're in progress, adds the next occurrence of the recurring ones
+    /// that were completed and records when they were completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if none of the given ids belong to a Task, in which
+    /// case no Task is changed.
+    pub fn transition(
+        &mut self,
+        ids: &[usize],
+        state: State,
+        today: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> Result<Transition, TaskerFailure> {
+        let matches = self.change_state(ids, state)?;
+
+        let tracked = self.tracked_task().map(|task| task.id);
+
+        if state != State::Doing
+            && tracked.map_or(false, |id| matches.found.contains(&id))
+        {
+            self.stop_tracking(now)?;
+        }
+
+        let recurrences = self.spawn_recurrences(&matches.found, today, now);
+        self.record_completions(&matches.found, now);
+
+        let unblocked = if state == State::Done {
+            self.unblocked_by(&matches.found)
+        } else {
+            Vec::new()
+        };
+
+        Ok(Transition {
+            matches,
+            unblocked,
+            recurrences,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -42,4 +604,522 @@ mod tests {
             }
         );
     }
+
+    fn sample_to_do() -> ToDo {
+        ToDo::from(vec![
+            Task::create("First").id(0).build(),
+            Task::create("Second").id(1).build(),
+            Task::create("Third").id(2).build(),
+        ])
+    }
+
+    #[test]
+    fn resolve_ids_works() {
+        let todo = sample_to_do();
+        let selections = ["2,0-1".parse(), "last".parse(), "5".parse()]
+            .into_iter()
+            .collect::<Result<Vec<IdSelection>, _>>()
+            .expect("selections should be valid");
+
+        assert_eq!(todo.resolve_ids(&selections), vec![2, 0, 1, 5]);
+    }
+
+    #[test]
+    fn resolve_ids_only_covers_existing_tasks_in_ranges() {
+        let todo = sample_to_do();
+        let selections = ["1-99999999999".parse(), "7".parse()]
+            .into_iter()
+            .collect::<Result<Vec<IdSelection>, _>>()
+            .expect("selections should be valid");
+
+        assert_eq!(todo.resolve_ids(&selections), vec![1, 2, 7]);
+    }
+
+    #[test]
+    fn resolve_ids_expands_all() {
+        let todo = sample_to_do();
+        let selections = vec![IdSelection(vec![IdSelector::All])];
+
+        assert_eq!(todo.resolve_ids(&selections), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn resolve_ids_ignores_last_on_empty_to_do() {
+        let todo = ToDo::default();
+        let selections = vec![IdSelection(vec![IdSelector::Last])];
+
+        assert!(todo.resolve_ids(&selections).is_empty());
+    }
+
+    #[test]
+    fn match_ids_splits_found_and_missing() {
+        let todo = sample_to_do();
+
+        let matches = todo
+            .match_ids(&[2, 7, 0, 2, 9])
+            .expect("some ids should match");
+
+        assert_eq!(
+            matches,
+            IdMatches {
+                found: vec![2, 0],
+                missing: vec![7, 9],
+            }
+        );
+    }
+
+    #[test]
+    fn match_ids_fails_when_nothing_matches() {
+        let todo = sample_to_do();
+
+        let result = todo.match_ids(&[5, 6]);
+
+        assert!(matches!(
+            result,
+            Err(TaskerFailure::TasksNotFound(ids)) if ids == vec![5, 6]
+        ));
+    }
+
+    #[test]
+    fn delete_tasks_only_removes_existing_tasks() {
+        let mut todo = sample_to_do();
+
+        let matches = todo.delete_tasks(&[1, 4]).expect("id 1 should exist");
+
+        assert_eq!(matches.found, vec![1]);
+        assert_eq!(matches.missing, vec![4]);
+        assert_eq!(
+            todo.tasks.iter().map(|task| task.id).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn clean_completed_detaches_remaining_tasks() {
+        let mut todo = ToDo::from(vec![
+            Task::create("Root").id(0).build(),
+            Task::create("Done parent")
+                .id(1)
+                .parent(0)
+                .state(State::Done)
+                .build(),
+            Task::create("Done child")
+                .id(2)
+                .parent(1)
+                .state(State::Done)
+                .build(),
+            Task::create("Open grandchild")
+                .id(3)
+                .parent(2)
+                .depends_on([1, 0])
+                .build(),
+        ]);
+
+        assert_eq!(todo.clean_completed(), vec![1, 2]);
+
+        let remaining = todo.get_task(3).expect("task 3 should remain");
+        assert_eq!(remaining.parent, Some(0));
+        assert_eq!(remaining.depends_on, IndexSet::from([0]));
+        assert_eq!(todo.next_id(), 4);
+        assert!(todo.clean_completed().is_empty());
+    }
+
+    #[test]
+    fn delete_tasks_leaves_to_do_untouched_on_error() {
+        let mut todo = sample_to_do();
+
+        assert!(todo.delete_tasks(&[8]).is_err());
+        assert_eq!(todo, sample_to_do());
+    }
+
+    fn sample_tree() -> ToDo {
+        ToDo::from(vec![
+            Task::create("Parent").id(0).build(),
+            Task::create("Child").id(1).parent(0).build(),
+            Task::create("Done child")
+                .id(2)
+                .parent(0)
+                .state(State::Done)
+                .build(),
+            Task::create("Grandchild").id(3).parent(1).build(),
+            Task::create("Unrelated").id(4).build(),
+        ])
+    }
+
+    #[test]
+    fn project_effort_works() {
+        let todo = ToDo::from(vec![
+            Task::create("First")
+                .project("Sprint")
+                .estimate(Estimate::Minutes(90))
+                .build(),
+            Task::create("Second")
+                .project("Sprint")
+                .estimate(Estimate::Points(3))
+                .build(),
+            Task::create("Third")
+                .project("Sprint")
+                .estimate(Estimate::Minutes(30))
+                .state(State::Done)
+                .build(),
+            Task::create("Fourth").project("Sprint").build(),
+            Task::create("Fifth")
+                .estimate(Estimate::Minutes(60))
+                .build(),
+        ]);
+
+        assert_eq!(
+            todo.project_effort("Sprint"),
+            (
+                Effort {
+                    minutes: 90,
+                    points: 3
+                },
+                Effort {
+                    minutes: 30,
+                    points: 0
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn subtask_progress_works() {
+        let todo = sample_tree();
+
+        assert_eq!(todo.subtask_progress(0), Some((1, 2)));
+        assert_eq!(todo.subtask_progress(1), Some((0, 1)));
+        assert_eq!(todo.subtask_progress(4), None);
+    }
+
+    #[test]
+    fn open_descendants_works() {
+        let todo = sample_tree();
+
+        assert_eq!(todo.open_descendants(&[0]), vec![1, 3]);
+        assert_eq!(todo.open_descendants(&[0, 1]), vec![3]);
+        assert!(todo.open_descendants(&[4]).is_empty());
+    }
+
+    #[test]
+    fn set_parent_works() {
+        let mut todo = sample_tree();
+
+        todo.set_parent(4, Some(3)).expect("parent should be valid");
+        todo.set_parent(1, None)
+            .expect("1 can become a top level Task");
+
+        assert_eq!(todo.get_task(4).and_then(|task| task.parent), Some(3));
+        assert_eq!(todo.get_task(1).and_then(|task| task.parent), None);
+    }
+
+    #[test]
+    fn set_parent_detects_cycles() {
+        let mut todo = sample_tree();
+
+        assert!(matches!(
+            todo.set_parent(0, Some(3)),
+            Err(TaskerFailure::SubtaskCycle(cycle)) if cycle == vec![0, 3, 1, 0]
+        ));
+        assert!(matches!(
+            todo.set_parent(4, Some(4)),
+            Err(TaskerFailure::SubtaskCycle(cycle)) if cycle == vec![4, 4]
+        ));
+        assert!(matches!(
+            todo.set_parent(4, Some(9)),
+            Err(TaskerFailure::TasksNotFound(ids)) if ids == vec![9]
+        ));
+    }
+
+    #[test]
+    fn delete_tasks_reparents_subtasks() {
+        let mut todo = sample_tree();
+
+        todo.delete_tasks(&[1]).expect("id 1 should exist");
+
+        let grandchild = todo
+            .tasks
+            .iter()
+            .find(|task| task.id == 3)
+            .expect("grandchild should still exist");
+
+        assert_eq!(grandchild.parent, Some(0));
+    }
+
+    #[test]
+    fn set_dependencies_works() {
+        let mut todo = sample_to_do();
+
+        todo.set_dependencies(2, [0, 1])
+            .expect("dependencies should be valid");
+
+        assert_eq!(
+            todo.get_task(2).map(|task| task.depends_on.clone()),
+            Some(IndexSet::from([0, 1]))
+        );
+    }
+
+    #[test]
+    fn set_dependencies_rejects_missing_tasks() {
+        let mut todo = sample_to_do();
+
+        assert!(matches!(
+            todo.set_dependencies(2, [0, 7]),
+            Err(TaskerFailure::TasksNotFound(ids)) if ids == vec![7]
+        ));
+    }
+
+    #[test]
+    fn set_dependencies_detects_cycles() {
+        let mut todo = sample_to_do();
+
+        todo.set_dependencies(1, [0]).expect("1 can depend on 0");
+        todo.set_dependencies(2, [1]).expect("2 can depend on 1");
+
+        assert!(matches!(
+            todo.set_dependencies(0, [2]),
+            Err(TaskerFailure::DependencyCycle(cycle)) if cycle == vec![0, 2, 1, 0]
+        ));
+        assert!(matches!(
+            todo.set_dependencies(0, [0]),
+            Err(TaskerFailure::DependencyCycle(cycle)) if cycle == vec![0, 0]
+        ));
+        assert!(todo
+            .get_task(0)
+            .is_some_and(|task| task.depends_on.is_empty()));
+    }
+
+    #[test]
+    fn blocked_state_follows_dependencies() {
+        let mut todo = sample_to_do();
+        todo.set_dependencies(2, [0, 1])
+            .expect("dependencies should be valid");
+
+        let task = todo.get_task(2).cloned().expect("task 2 should exist");
+        assert_eq!(todo.blockers(&task), vec![0, 1]);
+
+        todo.change_state(&[0], State::Done)
+            .expect("task 0 should exist");
+        assert!(todo.is_blocked(&task));
+        assert!(todo.unblocked_by(&[0]).is_empty());
+
+        todo.change_state(&[1], State::Done)
+            .expect("task 1 should exist");
+        assert!(!todo.is_blocked(&task));
+        assert_eq!(todo.unblocked_by(&[1]), vec![2]);
+    }
+
+    #[test]
+    fn delete_tasks_removes_dependencies() {
+        let mut todo = sample_to_do();
+        todo.set_dependencies(2, [0, 1])
+            .expect("dependencies should be valid");
+
+        todo.delete_tasks(&[0]).expect("task 0 should exist");
+
+        assert_eq!(
+            todo.get_task(2).map(|task| task.depends_on.clone()),
+            Some(IndexSet::from([1]))
+        );
+    }
+
+    #[test]
+    fn next_id_works() {
+        assert_eq!(ToDo::default().next_id(), 0);
+        assert_eq!(sample_to_do().next_id(), 3);
+    }
+
+    #[test]
+    fn spawn_recurrences_works() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 5).expect("valid date");
+        let now = DateTime::from_timestamp(1_717_600_000, 0)
+            .expect("timestamp is valid");
+        let mut todo = ToDo::from(vec![
+            Task::create("Standup")
+                .id(0)
+                .project("Work")
+                .tag("meetings")
+                .due(today)
+                .recurrence(Recurrence::Daily)
+                .build(),
+            Task::create("One-off").id(1).build(),
+        ]);
+
+        todo.change_state(&[0, 1], State::Done)
+            .expect("tasks should exist");
+
+        assert_eq!(todo.spawn_recurrences(&[0, 1], today, now), vec![2]);
+        assert!(todo.spawn_recurrences(&[0, 1], today, now).is_empty());
+        assert_eq!(
+            todo.get_task(2),
+            Some(
+                &Task::create("Standup")
+                    .id(2)
+                    .project("Work")
+                    .tag("meetings")
+                    .due(today.succ_opt().expect("valid date"))
+                    .recurrence(Recurrence::Daily)
+                    .created_at(now)
+                    .build()
+            )
+        );
+        assert_eq!(
+            todo.get_task(0).and_then(|task| task.recurrence.clone()),
+            None
+        );
+    }
+
+    #[test]
+    fn record_completions_works() {
+        let at = |seconds| {
+            DateTime::from_timestamp(seconds, 0).expect("timestamp is valid")
+        };
+        let mut todo = sample_to_do();
+
+        todo.change_state(&[0, 1, 2], State::Done)
+            .expect("tasks should exist");
+        todo.record_completions(&[0, 1], at(100));
+        todo.change_state(&[1], State::ToDo)
+            .expect("task should exist");
+        todo.record_completions(&[0, 1, 2], at(200));
+
+        assert_eq!(
+            todo.tasks
+                .iter()
+                .map(|task| task.completed_at)
+                .collect::<Vec<_>>(),
+            vec![Some(at(100)), None, Some(at(200))]
+        );
+    }
+
+    #[test]
+    fn time_tracking_works() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 5).expect("valid date");
+        let at = |seconds| {
+            DateTime::from_timestamp(seconds, 0).expect("timestamp is valid")
+        };
+        let mut todo = sample_to_do();
+
+        assert!(matches!(
+            todo.stop_tracking(at(0)),
+            Err(TaskerFailure::NotTracking)
+        ));
+
+        todo.start_tracking(1, today, at(0))
+            .expect("task 1 should exist");
+
+        assert!(matches!(
+            todo.start_tracking(2, today, at(10)),
+            Err(TaskerFailure::AlreadyTracking(1))
+        ));
+        assert_eq!(todo.tracked_task().map(|task| task.id), Some(1));
+        assert_eq!(todo.get_task(1).map(|task| task.state), Some(State::Doing));
+
+        assert_eq!(todo.stop_tracking(at(60)).ok(), Some(1));
+        assert!(todo.tracked_task().is_none());
+        assert_eq!(
+            todo.get_task(1).map(|task| task.time_log.clone()),
+            Some(vec![TimeInterval {
+                start: at(0),
+                end: Some(at(60)),
+            }])
+        );
+    }
+
+    #[test]
+    fn change_state_works() {
+        let mut todo = sample_to_do();
+
+        let matches = todo
+            .change_state(&[0, 2, 3], State::Done)
+            .expect("ids 0 and 2 should exist");
+
+        assert_eq!(matches.missing, vec![3]);
+        assert_eq!(
+            todo.tasks.iter().map(|task| task.state).collect::<Vec<_>>(),
+            vec![State::Done, State::ToDo, State::Done]
+        );
+    }
+
+    #[test]
+    fn transition_works() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 5).expect("valid date");
+        let at = |seconds| {
+            DateTime::from_timestamp(seconds, 0).expect("timestamp is valid")
+        };
+        let mut todo = sample_to_do();
+
+        todo.tasks[0].recurrence = Some(Recurrence::Daily);
+        todo.set_dependencies(1, [0]).expect("tasks should exist");
+        todo.start_tracking(0, today, at(0))
+            .expect("task 0 should exist");
+
+        let transition = todo
+            .transition(&[0, 5], State::Done, today, at(60))
+            .expect("task 0 should exist");
+
+        assert_eq!(
+            transition,
+            Transition {
+                matches: IdMatches {
+                    found: vec![0],
+                    missing: vec![5],
+                },
+                unblocked: vec![1],
+                recurrences: vec![3],
+            }
+        );
+        assert!(todo.tracked_task().is_none());
+        assert_eq!(
+            todo.get_task(0).and_then(|task| task.completed_at),
+            Some(at(60))
+        );
+
+        let transition = todo
+            .transition(&[0], State::ToDo, today, at(120))
+            .expect("task 0 should exist");
+
+        assert!(transition.unblocked.is_empty());
+        assert!(transition.recurrences.is_empty());
+        assert_eq!(todo.get_task(0).and_then(|task| task.completed_at), None);
+    }
+
+    #[test]
+    fn start_tracking_transitions_to_doing() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 5).expect("valid date");
+        let at = |seconds| {
+            DateTime::from_timestamp(seconds, 0).expect("timestamp is valid")
+        };
+        let mut tracked = sample_to_do();
+
+        tracked.tasks[0].recurrence = Some(Recurrence::Daily);
+        tracked
+            .set_dependencies(1, [0])
+            .expect("tasks should exist");
+        tracked
+            .transition(&[0], State::Done, today, at(0))
+            .expect("task 0 should exist");
+
+        let mut toggled = tracked.clone();
+
+        let started = tracked
+            .start_tracking(0, today, at(60))
+            .expect("task 0 should exist");
+        let transition = toggled
+            .transition(&[0], State::Doing, today, at(60))
+            .expect("task 0 should exist");
+
+        assert_eq!(started, transition);
+        assert_eq!(tracked.tracked_task().map(|task| task.id), Some(0));
+
+        tracked.tasks[0].time_log.clear();
+
+        assert_eq!(tracked, toggled);
+        assert_eq!(
+            tracked
+                .get_task(0)
+                .map(|task| (task.state, task.completed_at)),
+            Some((State::Doing, None))
+        );
+    }
 }
@@ -0,0 +1,231 @@
+use crate::error::TaskerFailure;
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// How often a Task repeats.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub enum Recurrence {
+    /// Every day, e.g. `daily`.
+    Daily,
+    /// Every week on the given weekdays, e.g. `weekly:mon,thu`. Without
+    /// weekdays it repeats on the same weekday it was due.
+    Weekly(Vec<Weekday>),
+    /// Every month on the given day, e.g. `monthly:15`. Months without that
+    /// day use their last one instead.
+    Monthly(u32),
+    /// The given number of days after the Task was completed, e.g. `every:3`.
+    AfterCompletion(u32),
+}
+
+impl Recurrence {
+    /// Returns the first date on or after the given one on which a Task with
+    /// this recurrence is due.
+    #[must_use]
+    pub fn first_on_or_after(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily | Self::AfterCompletion(_) => date,
+            Self::Weekly(weekdays) if weekdays.is_empty() => date,
+            Self::Weekly(weekdays) => date
+                .iter_days()
+                .take(7)
+                .find(|day| weekdays.contains(&day.weekday()))
+                .unwrap_or(date),
+            Self::Monthly(day) => {
+                let this_month = day_of_month(date, *day);
+
+                if this_month >= date {
+                    this_month
+                } else {
+                    date.checked_add_months(Months::new(1))
+                        .map_or(date, |next| day_of_month(next, *day))
+                }
+            }
+        }
+    }
+
+    /// Returns the date on which the next occurrence of a Task is due, given
+    /// the date its current occurrence was due and the date it was completed.
+    /// Occurrences that would already be overdue are skipped.
+    #[must_use]
+    pub fn next_occurrence(
+        &self,
+        due: NaiveDate,
+        completed: NaiveDate,
+    ) -> NaiveDate {
+        match self {
+            Self::AfterCompletion(days) => completed
+                .checked_add_days(Days::new(u64::from(*days)))
+                .unwrap_or(completed),
+            Self::Weekly(weekdays) if weekdays.is_empty() => {
+                let mut next = due + Days::new(7);
+
+                while next < completed {
+                    next = next + Days::new(7);
+                }
+
+                next
+            }
+            _ => self.first_on_or_after((due + Days::new(1)).max(completed)),
+        }
+    }
+}
+
+/// Returns the given day within the month of the given date, or the last day
+/// of that month if it doesn't have that many days.
+fn day_of_month(date: NaiveDate, day: u32) -> NaiveDate {
+    (1..=day.clamp(1, 31))
+        .rev()
+        .find_map(|day| date.with_day(day))
+        .unwrap_or(date)
+}
+
+impl FromStr for Recurrence {
+    type Err = TaskerFailure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim().to_lowercase();
+        let invalid =
+            |reason| TaskerFailure::InvalidRecurrence(s.to_string(), reason);
+
+        let (kind, value) = match rule.split_once(':') {
+            Some((kind, value)) => (kind.trim(), Some(value.trim())),
+            None => (rule.as_str(), None),
+        };
+
+        match (kind, value) {
+            ("daily", None) => Ok(Self::Daily),
+            ("weekly", None) => Ok(Self::Weekly(Vec::new())),
+            ("weekly", Some(weekdays)) => weekdays
+                .split(',')
+                .map(|weekday| weekday.trim().parse::<Weekday>())
+                .collect::<Result<_, _>>()
+                .map(Self::Weekly)
+                .map_err(|_| invalid("expected weekdays like `mon,thu`")),
+            ("monthly", Some(day)) => match day.parse() {
+                Ok(day @ 1..=31) => Ok(Self::Monthly(day)),
+                _ => Err(invalid("expected a day of the month from 1 to 31")),
+            },
+            ("every", Some(days)) => {
+                match days.trim_end_matches('d').parse() {
+                    Ok(days @ 1..) => Ok(Self::AfterCompletion(days)),
+                    _ => Err(invalid("expected a number of days like `3`")),
+                }
+            }
+            _ => Err(invalid(
+                "expected `daily`, `weekly[:mon,thu]`, `monthly:15` or `every:3`",
+            )),
+        }
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly(weekdays) if weekdays.is_empty() => {
+                write!(f, "weekly")
+            }
+            Self::Weekly(weekdays) => write!(
+                f,
+                "weekly:{}",
+                weekdays
+                    .iter()
+                    .map(|weekday| weekday.to_string().to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Self::Monthly(day) => write!(f, "monthly:{day}"),
+            Self::AfterCompletion(days) => write!(f, "every:{days}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("date should be valid")
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!("daily".parse::<Recurrence>().ok(), Some(Recurrence::Daily));
+        assert_eq!(
+            "Weekly:Mon, fri".parse::<Recurrence>().ok(),
+            Some(Recurrence::Weekly(vec![Weekday::Mon, Weekday::Fri]))
+        );
+        assert_eq!(
+            "monthly:15".parse::<Recurrence>().ok(),
+            Some(Recurrence::Monthly(15))
+        );
+        assert_eq!(
+            "every:3d".parse::<Recurrence>().ok(),
+            Some(Recurrence::AfterCompletion(3))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert!("hourly".parse::<Recurrence>().is_err());
+        assert!("weekly:someday".parse::<Recurrence>().is_err());
+        assert!("monthly:32".parse::<Recurrence>().is_err());
+        assert!("every:0".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for rule in
+            ["daily", "weekly", "weekly:mon,fri", "monthly:31", "every:3"]
+        {
+            assert_eq!(
+                rule.parse::<Recurrence>().map(|rule| rule.to_string()).ok(),
+                Some(rule.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn weekly_finds_next_weekday() {
+        let rule = Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu]);
+        // 2024-06-04 is a Tuesday.
+        let due = rule.first_on_or_after(date(2024, 6, 4));
+
+        assert_eq!(due, date(2024, 6, 6));
+        assert_eq!(rule.next_occurrence(due, due), date(2024, 6, 10));
+    }
+
+    #[test]
+    fn monthly_clamps_to_last_day() {
+        let rule = Recurrence::Monthly(31);
+        let due = rule.first_on_or_after(date(2024, 2, 10));
+
+        assert_eq!(due, date(2024, 2, 29));
+        assert_eq!(rule.next_occurrence(due, due), date(2024, 3, 31));
+    }
+
+    #[test]
+    fn after_completion_counts_from_completion() {
+        let rule = Recurrence::AfterCompletion(3);
+
+        assert_eq!(
+            rule.next_occurrence(date(2024, 6, 1), date(2024, 6, 5)),
+            date(2024, 6, 8)
+        );
+    }
+
+    #[test]
+    fn overdue_occurrences_are_skipped() {
+        assert_eq!(
+            Recurrence::Daily
+                .next_occurrence(date(2024, 6, 1), date(2024, 6, 5)),
+            date(2024, 6, 5)
+        );
+        assert_eq!(
+            Recurrence::Weekly(Vec::new())
+                .next_occurrence(date(2024, 6, 1), date(2024, 6, 20)),
+            date(2024, 6, 22)
+        );
+    }
+}
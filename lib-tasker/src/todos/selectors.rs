@@ -0,0 +1,120 @@
+use crate::error::TaskerFailure;
+use std::str::FromStr;
+
+/// A single way of referring to one or more Tasks by their ids.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IdSelector {
+    /// A single id, e.g. `3`.
+    Id(usize),
+    /// An inclusive range of ids, e.g. `3-8`.
+    Range(usize, usize),
+    /// The Task with the highest id.
+    Last,
+    /// Every Task.
+    All,
+}
+
+/// A comma separated list of id selectors, e.g. `1,4,9-12`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IdSelection(pub Vec<IdSelector>);
+
+impl FromStr for IdSelector {
+    type Err = TaskerFailure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let selector = s.trim();
+        let invalid = |reason| {
+            TaskerFailure::InvalidIdSelector(selector.to_string(), reason)
+        };
+
+        match selector.to_lowercase().as_str() {
+            "" => return Err(invalid("selector is empty")),
+            "last" => return Ok(Self::Last),
+            "all" => return Ok(Self::All),
+            _ => {}
+        }
+
+        if let Some((start, end)) = selector.split_once('-') {
+            let start = start
+                .trim()
+                .parse()
+                .map_err(|_| invalid("range start is not a valid id"))?;
+            let end = end
+                .trim()
+                .parse()
+                .map_err(|_| invalid("range end is not a valid id"))?;
+
+            if start > end {
+                return Err(invalid("range start is greater than its end"));
+            }
+
+            return Ok(Self::Range(start, end));
+        }
+
+        selector.parse().map(Self::Id).map_err(|_| {
+            invalid("expected an id, a range like `3-8`, `last` or `all`")
+        })
+    }
+}
+
+impl FromStr for IdSelection {
+    type Err = TaskerFailure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(IdSelector::from_str)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_ids() {
+        assert_eq!("7".parse::<IdSelector>().ok(), Some(IdSelector::Id(7)));
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(
+            "3-8".parse::<IdSelector>().ok(),
+            Some(IdSelector::Range(3, 8))
+        );
+    }
+
+    #[test]
+    fn parses_keywords() {
+        assert_eq!("last".parse::<IdSelector>().ok(), Some(IdSelector::Last));
+        assert_eq!("ALL".parse::<IdSelector>().ok(), Some(IdSelector::All));
+    }
+
+    #[test]
+    fn parses_comma_lists() {
+        assert_eq!(
+            "1,4,9-12".parse::<IdSelection>().ok(),
+            Some(IdSelection(vec![
+                IdSelector::Id(1),
+                IdSelector::Id(4),
+                IdSelector::Range(9, 12),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_reversed_ranges() {
+        assert!(matches!(
+            "8-3".parse::<IdSelector>(),
+            Err(TaskerFailure::InvalidIdSelector(selector, _)) if selector == "8-3"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        assert!("first".parse::<IdSelector>().is_err());
+        assert!("3-".parse::<IdSelector>().is_err());
+        assert!("1,,2".parse::<IdSelection>().is_err());
+    }
+}
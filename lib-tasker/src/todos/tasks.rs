@@ -1,3 +1,5 @@
+use super::{Estimate, Recurrence};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use indexmap::IndexSet;
 use serde::{Deserialize, Serialize};
 use str_slug::slug;
@@ -9,6 +11,62 @@ pub struct Task {
     pub state: State,
     pub tags: IndexSet<String>,
     pub project: String,
+    #[serde(default)]
+    pub parent: Option<usize>,
+    #[serde(default)]
+    pub depends_on: IndexSet<usize>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub estimate: Option<Estimate>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub time_log: Vec<TimeInterval>,
+    /// When each pomodoro spent on this Task was completed.
+    #[serde(default)]
+    pub pomodoros: Vec<DateTime<Utc>>,
+    /// When this Task was added. Tasks added before this was recorded don't
+    /// have it.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When this Task was last marked as done, if it currently is.
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A period of time spent working on a Task. It's still running if it has no
+/// end.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub struct TimeInterval {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeInterval {
+    /// Returns how much of this interval falls between `from` and `until`,
+    /// counting a running interval up to `now`.
+    #[must_use]
+    pub fn overlap(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Duration {
+        let start = self.start.max(from);
+        let end = self.end.unwrap_or(now).min(until);
+
+        (end - start).max(Duration::zero())
+    }
+}
+
+/// A timestamped note attached to a Task.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct Annotation {
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
 }
 
 #[derive(
@@ -39,9 +97,53 @@ impl Task {
             state: State::default(),
             tags: None,
             project: None,
+            parent: None,
+            depends_on: IndexSet::new(),
+            due: None,
+            recurrence: None,
+            estimate: None,
+            created_at: None,
         }
     }
 
+    pub fn annotate(
+        &mut self,
+        text: impl Into<String>,
+        timestamp: DateTime<Utc>,
+    ) {
+        self.annotations.push(Annotation {
+            timestamp,
+            text: text.into(),
+        });
+    }
+
+    pub fn log_pomodoro(&mut self, completed: DateTime<Utc>) {
+        self.pomodoros.push(completed);
+    }
+
+    /// Whether time is currently being tracked for this Task.
+    #[must_use]
+    pub fn is_tracking(&self) -> bool {
+        self.time_log
+            .last()
+            .map_or(false, |interval| interval.end.is_none())
+    }
+
+    /// Returns the time spent on this Task between `from` and `until`,
+    /// counting a running interval up to `now`.
+    #[must_use]
+    pub fn tracked_time(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Duration {
+        self.time_log
+            .iter()
+            .map(|interval| interval.overlap(from, until, now))
+            .fold(Duration::zero(), |total, time| total + time)
+    }
+
     pub fn add_tag(&mut self, tag: impl Into<String>) {
         self.tags.insert(slug(tag.into()));
     }
@@ -77,6 +179,12 @@ pub struct TaskBuilder {
     state: State,
     tags: Option<IndexSet<String>>,
     project: Option<String>,
+    parent: Option<usize>,
+    depends_on: IndexSet<usize>,
+    due: Option<NaiveDate>,
+    recurrence: Option<Recurrence>,
+    estimate: Option<Estimate>,
+    created_at: Option<DateTime<Utc>>,
 }
 
 impl TaskBuilder {
@@ -95,6 +203,39 @@ impl TaskBuilder {
         self
     }
 
+    pub fn parent(&mut self, parent: usize) -> &mut Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn depends_on(
+        &mut self,
+        ids: impl IntoIterator<Item = usize>,
+    ) -> &mut Self {
+        self.depends_on = ids.into_iter().collect();
+        self
+    }
+
+    pub fn due(&mut self, due: NaiveDate) -> &mut Self {
+        self.due = Some(due);
+        self
+    }
+
+    pub fn recurrence(&mut self, recurrence: Recurrence) -> &mut Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    pub fn estimate(&mut self, estimate: Estimate) -> &mut Self {
+        self.estimate = Some(estimate);
+        self
+    }
+
+    pub fn created_at(&mut self, created_at: DateTime<Utc>) -> &mut Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
     pub fn tag(&mut self, tag: impl Into<String>) -> &mut Self {
         if self.tags.is_none() {
             let mut tags = IndexSet::new();
@@ -128,6 +269,16 @@ impl TaskBuilder {
                 .project
                 .clone()
                 .unwrap_or_else(|| "Inbox".to_string()),
+            parent: self.parent,
+            depends_on: self.depends_on.clone(),
+            due: self.due,
+            recurrence: self.recurrence.clone(),
+            estimate: self.estimate,
+            annotations: Vec::new(),
+            time_log: Vec::new(),
+            pomodoros: Vec::new(),
+            created_at: self.created_at,
+            completed_at: None,
         }
     }
 }
@@ -147,7 +298,17 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::ToDo,
                 tags: IndexSet::new(),
-                project: "Inbox".to_string()
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
@@ -163,7 +324,17 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::Waiting,
                 tags: IndexSet::new(),
-                project: "Inbox".to_string()
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
@@ -181,7 +352,17 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::ToDo,
                 tags: set,
-                project: "Inbox".to_string()
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
@@ -203,7 +384,17 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::ToDo,
                 tags: set,
-                project: "Inbox".to_string()
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
@@ -219,11 +410,112 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::ToDo,
                 tags: IndexSet::new(),
-                project: "Testing".to_string()
+                project: "Testing".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
+            }
+        );
+    }
+
+    #[test]
+    fn task_builder_change_parent_works() {
+        let task = Task::create("This is a test").parent(3).build();
+
+        assert_eq!(
+            task,
+            Task {
+                id: 0,
+                description: "This is a test".to_string(),
+                state: State::ToDo,
+                tags: IndexSet::new(),
+                project: "Inbox".to_string(),
+                parent: Some(3),
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
 
+    #[test]
+    fn task_builder_change_dependencies_works() {
+        let task = Task::create("This is a test").depends_on([2, 5]).build();
+
+        assert_eq!(
+            task,
+            Task {
+                id: 0,
+                description: "This is a test".to_string(),
+                state: State::ToDo,
+                tags: IndexSet::new(),
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::from([2, 5]),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
+            }
+        );
+    }
+
+    #[test]
+    fn task_builder_change_recurrence_works() {
+        let due = NaiveDate::from_ymd_opt(2024, 6, 3).expect("valid date");
+        let task = Task::create("This is a test")
+            .due(due)
+            .recurrence(Recurrence::Daily)
+            .build();
+
+        assert_eq!(
+            task,
+            Task {
+                id: 0,
+                description: "This is a test".to_string(),
+                state: State::ToDo,
+                tags: IndexSet::new(),
+                project: "Inbox".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: Some(due),
+                recurrence: Some(Recurrence::Daily),
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
+            }
+        );
+    }
+
+    #[test]
+    fn task_builder_change_estimate_works() {
+        let task = Task::create("This is a test")
+            .estimate(Estimate::Points(3))
+            .build();
+
+        assert_eq!(task.estimate, Some(Estimate::Points(3)));
+    }
+
     #[test]
     fn add_tag_works() {
         let mut task =
@@ -237,11 +529,81 @@ mod tests {
                 description: "This is a test".to_string(),
                 state: State::ToDo,
                 tags: IndexSet::from(["testing-tags".to_string()]),
-                project: "Testing".to_string()
+                project: "Testing".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
 
+    #[test]
+    fn annotate_works() {
+        let mut task = Task::create("This is a test").build();
+        let timestamp = DateTime::from_timestamp(1_717_200_000, 0)
+            .expect("timestamp should be valid");
+
+        task.annotate("Waiting on review", timestamp);
+
+        assert_eq!(
+            task.annotations,
+            vec![Annotation {
+                timestamp,
+                text: "Waiting on review".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn tracked_time_works() {
+        let at = |seconds| {
+            DateTime::from_timestamp(seconds, 0).expect("timestamp is valid")
+        };
+        let mut task = Task::create("This is a test").build();
+        task.time_log = vec![
+            TimeInterval {
+                start: at(0),
+                end: Some(at(600)),
+            },
+            TimeInterval {
+                start: at(1_000),
+                end: None,
+            },
+        ];
+
+        assert!(task.is_tracking());
+        assert_eq!(
+            task.tracked_time(at(0), at(10_000), at(1_300)),
+            Duration::seconds(900)
+        );
+        assert_eq!(
+            task.tracked_time(at(300), at(1_100), at(1_300)),
+            Duration::seconds(400)
+        );
+        assert_eq!(
+            task.tracked_time(at(2_000), at(3_000), at(1_300)),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn log_pomodoro_works() {
+        let completed =
+            DateTime::from_timestamp(1_500, 0).expect("timestamp is valid");
+        let mut task = Task::create("This is a test").build();
+        task.log_pomodoro(completed);
+        task.log_pomodoro(completed);
+
+        assert_eq!(task.pomodoros, vec![completed, completed]);
+    }
+
     #[test]
     fn add_tags_works() {
         let mut task =
@@ -259,7 +621,17 @@ mod tests {
                     "another-tag".to_string(),
                     "yet-another-tag".to_string()
                 ]),
-                project: "Testing".to_string()
+                project: "Testing".to_string(),
+                parent: None,
+                depends_on: IndexSet::new(),
+                due: None,
+                recurrence: None,
+                estimate: None,
+                annotations: Vec::new(),
+                time_log: Vec::new(),
+                pomodoros: Vec::new(),
+                created_at: None,
+                completed_at: None
             }
         );
     }
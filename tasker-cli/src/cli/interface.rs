@@ -1,6 +1,15 @@
+use crate::config::ListFormat;
+use anstream::ColorChoice;
 use camino::Utf8PathBuf;
-use clap::{Args, Parser, Subcommand, ValueEnum};
-use lib_tasker::todos::State;
+use chrono::NaiveDate;
+use clap::{
+    builder::RangedU64ValueParser, Args, Parser, Subcommand, ValueEnum,
+};
+use lib_tasker::{
+    statistics::Period,
+    todos::{Estimate, Filter, IdSelection, Recurrence, State},
+};
+use std::str::FromStr;
 
 /// A command-line application to manage your daily Tasks.
 #[derive(Debug, Parser)]
@@ -26,9 +35,37 @@ pub struct Cli {
     #[arg(short = 'T', long)]
     pub todo_file: Option<Utf8PathBuf>,
 
-    /// Path to an alternative configuration file. Takes precedence over `todo-file`
+    /// Path to a configuration file to use instead of the user one. Settings
+    /// from the system, a local `.tasker.toml`, `TASKER_*` environment
+    /// variables and `todo-file` still apply on top
     #[arg(short = 'C', long)]
     pub config_file: Option<Utf8PathBuf>,
+
+    /// Workspace to use instead of the one selected with `switch`
+    #[arg(short = 'W', long, global = true)]
+    pub workspace: Option<String>,
+
+    /// When to use colours. `auto` leaves them out if `NO_COLOR` is set or if
+    /// the output isn't a terminal
+    #[arg(long, value_enum, global = true, default_value_t = ColorWhen::Auto)]
+    pub color: ColorWhen,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum ColorWhen {
+    Always,
+    Never,
+    Auto,
+}
+
+impl From<ColorWhen> for ColorChoice {
+    fn from(value: ColorWhen) -> Self {
+        match value {
+            ColorWhen::Always => Self::Always,
+            ColorWhen::Never => Self::Never,
+            ColorWhen::Auto => Self::Auto,
+        }
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -45,29 +82,93 @@ pub enum Command {
     #[command(arg_required_else_help = true, visible_alias = "a")]
     Add(AddTasks),
 
+    /// Attach a note to a Task
+    #[command(arg_required_else_help = true, visible_alias = "n")]
+    Annotate(AnnotateTask),
+
+    /// Show Tasks in columns by state
+    #[command(visible_alias = "b")]
+    Board(ListTasks),
+
     /// Clean completed Tasks
     #[command(visible_alias = "c")]
     Clean,
 
+    /// Read and change settings of the configuration file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Select a saved filter that narrows down listings and fills in new
+    /// Tasks
+    #[command(subcommand)]
+    Context(ContextCommand),
+
     /// Delete Tasks
     #[command(arg_required_else_help = true, visible_alias = "d")]
     Delete(DeleteTasks),
 
-    /// Edit a Task
+    /// Edit Task(s)
     #[command(arg_required_else_help = true, visible_alias = "e")]
     Edit(EditTask),
 
+    /// Work on a Task in pomodoro cycles
+    #[command(arg_required_else_help = true, visible_alias = "f")]
+    Focus(FocusTask),
+
+    /// Create a Tasks file in the current directory, used instead of the
+    /// global one from it and every directory below
+    Init(InitToDo),
+
     /// List Tasks
     #[command(visible_alias = "l")]
-    List(ListTasks),
+    List(ListCommand),
+
+    /// List the workspaces, each with its Tasks file
+    #[command(visible_alias = "lw")]
+    ListWorkspaces,
 
-    /// Print default paths for the application
+    /// Print the paths of the application, and which Tasks file is used and
+    /// why
     #[command(visible_alias = "p")]
     Paths,
 
+    /// Draw charts of your progress or run a custom report
+    #[command(subcommand, visible_alias = "r")]
+    Report(Report),
+
+    /// Start tracking time spent on a Task
+    #[command(arg_required_else_help = true)]
+    Start(StartTask),
+
+    /// Stop tracking time for the Task being tracked
+    Stop,
+
+    /// Show every detail of Task(s), including their notes
+    #[command(arg_required_else_help = true, visible_alias = "s")]
+    Show(ShowTasks),
+
+    /// Show statistics about your Tasks
+    Stats(ShowStats),
+
+    /// Select the workspace to use from now on. `default` goes back to the
+    /// top-level settings
+    #[command(arg_required_else_help = true)]
+    Switch(SwitchWorkspace),
+
+    /// Summarize the time spent on Tasks, projects and tags
+    #[command(visible_alias = "ts")]
+    Timesheet(Timesheet),
+
     /// Change the state of a Task
     #[command(arg_required_else_help = true, visible_alias = "t")]
     Toggle(ToggleTasks),
+
+    /// Open a full-screen terminal interface
+    Tui,
+
+    /// Run a custom report from the configuration file by its name
+    #[command(external_subcommand)]
+    Custom(Vec<String>),
 }
 
 #[derive(Args, Debug)]
@@ -90,6 +191,415 @@ pub struct AddTasks {
     /// Tag to assign the Task(s). Can be called multiple times
     #[arg(short, long)]
     pub tag: Option<Vec<String>>,
+
+    /// ID of the Task these will be subtasks of. They inherit its project
+    /// unless `project` is given
+    #[arg(long)]
+    pub parent: Option<usize>,
+
+    /// ID(s) of the Task(s) that must be done before starting these. Accepts
+    /// ranges and lists like `3-8` or `1,4,9-12`
+    #[arg(short = 'D', long)]
+    pub depends: Option<Vec<IdSelection>>,
+
+    /// Repeat the Task(s) once completed: `daily`, `weekly[:mon,thu]`,
+    /// `monthly:15` or `every:3` days after completion
+    #[arg(short, long)]
+    pub recur: Option<Recurrence>,
+
+    /// Expected effort, as a duration like `1h30m` or points like `3pts`
+    #[arg(short, long)]
+    pub estimate: Option<Estimate>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct AnnotateTask {
+    /// ID of the Task to annotate
+    #[arg(name = "TO-DO")]
+    pub task: usize,
+
+    /// Text of the note
+    #[arg(required = true)]
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct FocusTask {
+    /// ID of the Task to work on
+    #[arg(name = "TO-DO")]
+    pub task: usize,
+
+    /// Number of pomodoros to complete before stopping. Runs until quit if
+    /// not given
+    #[arg(short, long)]
+    pub cycles: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct InitToDo {
+    /// Create a `.tasker` directory holding the file instead of a
+    /// `.tasker.ron` file
+    #[arg(short, long)]
+    pub directory: bool,
+}
+
+#[derive(Debug, Subcommand)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub enum ConfigCommand {
+    /// Print the value of a setting, or of every setting under a table
+    #[command(arg_required_else_help = true)]
+    Get(ConfigKey),
+
+    /// Change the value of a setting
+    #[command(arg_required_else_help = true)]
+    Set(SetSetting),
+
+    /// Remove a setting from the configuration file, going back to its default
+    #[command(arg_required_else_help = true)]
+    Unset(ConfigKey),
+
+    /// Print every setting with its current value
+    List(ListSettings),
+
+    /// Open the configuration file in `$VISUAL` or `$EDITOR`
+    Edit,
+
+    /// Bring the configuration file up to date: fix misspelt settings, remove
+    /// unknown ones and invalid values, and add missing ones with defaults
+    Doctor(Doctor),
+
+    /// Print the path of the configuration file
+    Path,
+}
+
+impl ConfigCommand {
+    /// Returns whether the subcommand helps to fix a broken configuration
+    /// file, so it has to run even if the configuration can't be loaded.
+    #[must_use]
+    pub const fn fixes_file(&self) -> bool {
+        matches!(
+            self,
+            Self::Set(_)
+                | Self::Unset(_)
+                | Self::Edit
+                | Self::Doctor(_)
+                | Self::Path
+        )
+    }
+}
+
+#[derive(Debug, Subcommand)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub enum ContextCommand {
+    /// Apply a context from the `contexts` table of the configuration file to
+    /// `list`, `board`, custom reports and the default listing, and give new
+    /// Tasks its project and tags
+    #[command(arg_required_else_help = true)]
+    Set(ContextName),
+
+    /// Stop applying the active context
+    Clear,
+
+    /// Print the active context and its filter
+    Show,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ContextName {
+    /// Name of the context
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ConfigKey {
+    /// Name of the setting, with dots for nested ones like `pomodoro.work`
+    pub key: String,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct Doctor {
+    /// Show what would change without saving it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ListSettings {
+    /// Show where each value came from: the defaults, a configuration file,
+    /// an environment variable or a flag
+    #[arg(long)]
+    pub show_origin: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct SetSetting {
+    /// Name of the setting, with dots for nested ones like `pomodoro.work`
+    pub key: String,
+
+    /// New value. Numbers, booleans and arrays like `["id", "state"]` are read
+    /// as TOML, anything else as text
+    #[arg(allow_hyphen_values = true)]
+    pub value: String,
+}
+
+#[derive(Debug, Subcommand)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub enum Report {
+    /// Chart how many Tasks were still open at the end of each day
+    Burndown(BurndownReport),
+
+    /// Chart how many Tasks were completed each day or week
+    Activity(ActivityReport),
+
+    /// Run a custom report from the configuration file by its name, narrowed
+    /// down by any filter terms given after it
+    #[command(external_subcommand)]
+    Custom(Vec<String>),
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct BurndownReport {
+    /// Only count Tasks belonging to this project
+    #[arg(short, long)]
+    pub project: Option<String>,
+
+    /// First day to chart, as YYYY-MM-DD. Defaults to two weeks ago
+    #[arg(short, long)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to chart, as YYYY-MM-DD. Defaults to today
+    #[arg(short, long)]
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ActivityReport {
+    /// Group completed Tasks by day or by week
+    #[arg(short, long, value_enum, default_value_t = ReportPeriod::Day)]
+    pub by: ReportPeriod,
+
+    /// First day to chart, as YYYY-MM-DD. Defaults to two weeks ago by day,
+    /// or eight weeks ago by week
+    #[arg(short, long)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to chart, as YYYY-MM-DD. Defaults to today
+    #[arg(short, long)]
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    /// One bar per day [aliases: d]
+    #[value(alias = "d")]
+    Day,
+
+    /// One bar per week, starting on Monday [aliases: w]
+    #[value(alias = "w")]
+    Week,
+}
+
+impl From<ReportPeriod> for Period {
+    fn from(value: ReportPeriod) -> Self {
+        match value {
+            ReportPeriod::Day => Self::Day,
+            ReportPeriod::Week => Self::Week,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct SwitchWorkspace {
+    /// Name of the workspace, from the `workspaces` table of the
+    /// configuration file
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct StartTask {
+    /// ID of the Task to work on
+    #[arg(name = "TO-DO")]
+    pub task: usize,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ShowStats {
+    /// Number of weeks of activity to show, up to 520
+    #[arg(
+        short,
+        long,
+        default_value_t = 8,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..=520)
+    )]
+    pub weeks: usize,
+
+    /// Number of the oldest open Tasks to show
+    #[arg(short, long, default_value_t = 5)]
+    pub oldest: usize,
+
+    /// Print the statistics as JSON
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct Timesheet {
+    /// First day to include, as YYYY-MM-DD. Defaults to a week ago
+    #[arg(short, long)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to include, as YYYY-MM-DD. Defaults to today
+    #[arg(short, long)]
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ShowTasks {
+    /// ID(s) of the Task(s) to show. Accepts ranges and lists like `3-8` or
+    /// `1,4,9-12`, as well as `last` and `all`
+    #[arg(name = "TO-DOS")]
+    pub tasks: Vec<IdSelection>,
 }
 
 #[derive(Args, Debug)]
@@ -106,12 +616,17 @@ pub struct ToggleTasks {
     #[arg(value_enum)]
     pub state: ToggleState,
 
-    /// ID(s) of the Task(s) to toggle
+    /// ID(s) of the Task(s) to toggle. Accepts ranges and lists like
+    /// `3-8` or `1,4,9-12`, as well as `last` and `all`
     #[arg(name = "TO-DOS")]
-    pub tasks: Vec<usize>,
+    pub tasks: Vec<IdSelection>,
+
+    /// When completing Task(s), also complete their open subtasks
+    #[arg(short, long)]
+    pub cascade: bool,
 }
 
-#[derive(Debug, ValueEnum, Clone, Copy)]
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
 pub enum ToggleState {
     /// This Task hasn't started
     #[value(name = "todo", alias = "t")]
@@ -151,9 +666,18 @@ impl From<ToggleState> for State {
 {all-args}"
 ))]
 pub struct EditTask {
-    /// ID of the Task to edit
-    #[arg(name = "TO-DO")]
-    pub task: usize,
+    /// ID(s) of the Task(s) to edit. Accepts ranges and lists like `3-8`
+    /// or `1,4,9-12`, as well as `last` and `all`
+    #[arg(name = "TO-DOS", required = true)]
+    pub tasks: Vec<IdSelection>,
+
+    /// Edit the Task(s) in `$VISUAL` or `$EDITOR` instead
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["description", "state", "project", "tags", "depends", "estimate"]
+    )]
+    pub interactive: bool,
 
     /// New description
     #[arg(short, long)]
@@ -170,6 +694,15 @@ pub struct EditTask {
     /// New tags
     #[arg(short, long)]
     pub tags: Option<Vec<String>>,
+
+    /// New dependencies. Accepts ranges and lists like `3-8` or `1,4,9-12`
+    #[arg(short = 'D', long, num_args = 0..)]
+    pub depends: Option<Vec<IdSelection>>,
+
+    /// New estimate, as a duration like `1h30m` or points like `3pts`. Removes
+    /// it if given without a value
+    #[arg(short, long, num_args = 0..=1)]
+    pub estimate: Option<Option<Estimate>>,
 }
 
 #[derive(Args, Debug)]
@@ -182,9 +715,34 @@ pub struct EditTask {
 {all-args}"
 ))]
 pub struct DeleteTasks {
-    /// Ids of the Task(s) to delete
+    /// Ids of the Task(s) to delete. Accepts ranges and lists like `3-8` or
+    /// `1,4,9-12`, as well as `last` and `all`
     #[arg(name = "TASKS")]
-    pub tasks: Vec<usize>,
+    pub tasks: Vec<IdSelection>,
+}
+
+#[derive(Args, Debug)]
+#[command(help_template(
+    "\
+{name}
+{about-with-newline}
+{usage-heading} {usage}
+
+{all-args}"
+))]
+pub struct ListCommand {
+    #[command(flatten)]
+    pub options: ListTasks,
+
+    /// How to lay out each Task. Defaults to `list_format` in the
+    /// configuration file
+    #[arg(short = 'F', long, value_enum)]
+    pub format: Option<ListFormat>,
+
+    /// List the Tasks of every workspace, one per line, with their IDs
+    /// prefixed by the name of their workspace
+    #[arg(short = 'A', long, conflicts_with = "format")]
+    pub all_workspaces: bool,
 }
 
 #[derive(Args, Debug)]
@@ -197,9 +755,15 @@ pub struct DeleteTasks {
 {all-args}"
 ))]
 pub struct ListTasks {
-    /// Sort Tasks by this field
-    #[arg(short = 'S', long, value_enum)]
-    pub sort_by: Option<SortTasks>,
+    /// Sort Tasks by these fields, in order of priority. Append `:desc` to a
+    /// field to reverse it, like `project,estimate:desc`
+    #[arg(short = 'S', long, value_delimiter = ',')]
+    pub sort_by: Vec<SortKey>,
+
+    /// Only show Tasks matching this expression, like
+    /// `project:work and (+urgent or due:overdue)`
+    #[arg(short, long)]
+    pub filter: Option<Filter>,
 
     /// Only show Tasks containing this text within their descriptions
     #[arg(short, long)]
@@ -216,6 +780,18 @@ pub struct ListTasks {
     /// Only show Tasks belonging to this project
     #[arg(short, long)]
     pub project: Option<String>,
+
+    /// Only show Tasks waiting on unfinished dependencies
+    #[arg(short, long, conflicts_with = "unblocked")]
+    pub blocked: bool,
+
+    /// Only show Tasks that aren't waiting on unfinished dependencies
+    #[arg(short, long)]
+    pub unblocked: bool,
+
+    /// Only show Tasks without an estimate
+    #[arg(short = 'E', long)]
+    pub unestimated: bool,
 }
 
 #[derive(Debug, ValueEnum, Clone, Copy)]
@@ -235,4 +811,39 @@ pub enum SortTasks {
     /// Sort by ID [aliases: i]
     #[value(alias = "i")]
     ID,
+
+    /// Sort by estimate, leaving unestimated Tasks last [aliases: est, e]
+    #[value(alias = "est", alias = "e")]
+    Estimate,
+}
+
+/// A field to sort Tasks by, written as `field` or `field:desc`.
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: SortTasks,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = s.split_once(':').unwrap_or((s, "asc"));
+
+        let descending = match direction.trim().to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            direction => {
+                return Err(format!(
+                    "invalid direction `{direction}`, expected `asc` or `desc`"
+                ))
+            }
+        };
+
+        Ok(Self {
+            field: SortTasks::from_str(field.trim(), true)
+                .map_err(|_| format!("invalid sort field `{field}`"))?,
+            descending,
+        })
+    }
 }
@@ -0,0 +1,821 @@
+use super::{Cli, ColorWhen, ReportPeriod, SortTasks, ToggleState};
+use crate::{
+    config::{closest, sgr_codes, Configuration, Language, ListFormat, Theme},
+    i18n::{catalogue, Catalogue},
+};
+use camino::Utf8PathBuf;
+use clap::{
+    builder::{
+        styling::{self, Ansi256Color, AnsiColor, Color, RgbColor, Styles},
+        PossibleValue, TypedValueParser,
+    },
+    error::{ContextKind, ContextValue, ErrorKind},
+    Arg, ArgAction, Command, CommandFactory, FromArgMatches, ValueEnum,
+};
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use std::{
+    error::Error as _,
+    ffi::{OsStr, OsString},
+    fmt::Write,
+    marker::PhantomData,
+    path::Path,
+};
+
+/// A kind of value accepted by arguments, whose help texts can be translated
+/// and which can be written with names in the configured language.
+pub trait TranslatedValue: ValueEnum + Clone + Send + Sync + 'static {
+    /// Name of the kind of value in the `[values]` table of the catalogues.
+    const KEY: &'static str;
+}
+
+impl TranslatedValue for ColorWhen {
+    const KEY: &'static str = "color";
+}
+
+impl TranslatedValue for ListFormat {
+    const KEY: &'static str = "format";
+}
+
+impl TranslatedValue for ReportPeriod {
+    const KEY: &'static str = "period";
+}
+
+impl TranslatedValue for SortTasks {
+    const KEY: &'static str = "sort";
+}
+
+impl TranslatedValue for ToggleState {
+    const KEY: &'static str = "state";
+}
+
+impl Cli {
+    /// Parses the command line arguments like [`clap::Parser::parse`], but
+    /// with help texts, value names and errors in the language of the
+    /// configuration file.
+    #[must_use]
+    pub fn parse_localized() -> Self {
+        let args: Vec<OsString> = std::env::args_os().collect();
+        let config_file =
+            flag_argument(&args, "-C", "--config-file").map(Utf8PathBuf::from);
+        let workspace = flag_argument(&args, "-W", "--workspace");
+        let language = Configuration::peek_language(
+            config_file.as_deref(),
+            workspace.as_deref(),
+        );
+        let theme = Configuration::peek_theme(
+            config_file.as_deref(),
+            workspace.as_deref(),
+        );
+        let mut command = localized_command(language, &theme);
+
+        command
+            .try_get_matches_from_mut(args)
+            .and_then(|matches| Self::from_arg_matches(&matches))
+            .unwrap_or_else(|err| exit_with_error(&err, language, &theme))
+    }
+}
+
+/// Returns the command line interface with its help texts in the given
+/// language and its headings in the style of the given theme. English texts
+/// come from the documentation of each argument.
+#[must_use]
+pub fn localized_command(language: Language, theme: &Theme) -> Command {
+    let heading = clap_style(theme.heading());
+    let command =
+        Cli::command().styles(Styles::styled().header(heading).usage(heading));
+
+    if language == Language::English {
+        return command;
+    }
+
+    let catalogue = catalogue(language);
+    let usage = catalogue
+        .text("help_usage")
+        .style(theme.heading())
+        .to_string();
+
+    // Arguments can't be changed once clap builds the command, but the `help`
+    // subcommands only exist afterwards.
+    let mut command = translate_command(command, "", catalogue, &usage)
+        .disable_version_flag(true)
+        .arg(
+            Arg::new("version")
+                .short('V')
+                .long("version")
+                .action(ArgAction::Version)
+                .help(catalogue.text("version_flag"))
+                .help_heading(catalogue.text("help_options"))
+                .display_order(usize::MAX),
+        );
+    command.build();
+
+    translate_help_subcommands(command, catalogue)
+}
+
+/// Converts a style of the theme into one for the help texts of clap.
+fn clap_style(style: owo_colors::Style) -> styling::Style {
+    const ANSI: [AnsiColor; 16] = [
+        AnsiColor::Black,
+        AnsiColor::Red,
+        AnsiColor::Green,
+        AnsiColor::Yellow,
+        AnsiColor::Blue,
+        AnsiColor::Magenta,
+        AnsiColor::Cyan,
+        AnsiColor::White,
+        AnsiColor::BrightBlack,
+        AnsiColor::BrightRed,
+        AnsiColor::BrightGreen,
+        AnsiColor::BrightYellow,
+        AnsiColor::BrightBlue,
+        AnsiColor::BrightMagenta,
+        AnsiColor::BrightCyan,
+        AnsiColor::BrightWhite,
+    ];
+
+    let mut codes = sgr_codes(style).into_iter();
+    let mut converted = styling::Style::new();
+
+    while let Some(code) = codes.next() {
+        let ansi =
+            |offset: u8| Some(Color::from(ANSI[usize::from(code - offset)]));
+
+        converted = match code {
+            1 => converted.bold(),
+            2 => converted.dimmed(),
+            3 => converted.italic(),
+            4 => converted.underline(),
+            5 | 6 => converted.blink(),
+            7 => converted.invert(),
+            8 => converted.hidden(),
+            9 => converted.strikethrough(),
+            30..=37 => converted.fg_color(ansi(30)),
+            40..=47 => converted.bg_color(ansi(40)),
+            90..=97 => converted.fg_color(ansi(82)),
+            100..=107 => converted.bg_color(ansi(92)),
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => {
+                        codes.next().map(|index| Ansi256Color(index).into())
+                    }
+                    Some(2) => match (codes.next(), codes.next(), codes.next())
+                    {
+                        (Some(r), Some(g), Some(b)) => {
+                            Some(RgbColor(r, g, b).into())
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                if code == 38 {
+                    converted
+                        .fg_color(color.or_else(|| converted.get_fg_color()))
+                } else {
+                    converted
+                        .bg_color(color.or_else(|| converted.get_bg_color()))
+                }
+            }
+            _ => converted,
+        };
+    }
+
+    converted
+}
+
+/// Finds the value of the option with the given short and long names, such
+/// as `-C` and `--config-file`, before the arguments are parsed.
+fn flag_argument(args: &[OsString], short: &str, long: &str) -> Option<String> {
+    let mut args = args.iter().skip(1).filter_map(|arg| arg.to_str());
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            return None;
+        }
+
+        if arg == short || arg == long {
+            return args.next().map(ToString::to_string);
+        }
+
+        if let Some(value) = arg
+            .strip_prefix(long)
+            .and_then(|rest| rest.strip_prefix('='))
+            .or_else(|| arg.strip_prefix(short))
+            .filter(|value| !value.is_empty())
+        {
+            return Some(value.to_string());
+        }
+    }
+
+    None
+}
+
+fn translate_command(
+    mut command: Command,
+    path: &str,
+    catalogue: &'static Catalogue,
+    usage: &str,
+) -> Command {
+    let key = if path.is_empty() { "tasker" } else { path };
+
+    if let Some(about) = catalogue.help(key) {
+        command = command.about(about);
+    }
+
+    let template = if path.is_empty() {
+        format!(
+            "\
+{{before-help}}{{name}} {{version}}
+{{author-with-newline}}{{about-with-newline}}
+{usage} {{usage}}
+
+{{all-args}}{{after-help}}"
+        )
+    } else {
+        format!(
+            "\
+{{name}}
+{{about-with-newline}}
+{usage} {{usage}}
+
+{{all-args}}"
+        )
+    };
+
+    command = command
+        .help_template(template)
+        .subcommand_help_heading(catalogue.text("help_commands"))
+        .disable_help_flag(true);
+
+    let arguments: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .collect();
+
+    // Arguments under a custom heading are sorted by their display order, which
+    // clap leaves unset for positional ones.
+    for (position, id) in arguments.iter().enumerate() {
+        command = command.mut_arg(id, |arg| {
+            let arg = translate_argument(arg, path, catalogue);
+
+            if arg.is_positional() {
+                arg.display_order(position)
+            } else {
+                arg
+            }
+        });
+    }
+
+    command = command.arg(
+        Arg::new("help")
+            .short('h')
+            .long("help")
+            .action(ArgAction::Help)
+            .help(catalogue.text("help_flag"))
+            .help_heading(catalogue.text("help_options"))
+            .display_order(usize::MAX),
+    );
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+
+    for name in subcommands {
+        let path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{path}.{name}")
+        };
+
+        command = command.mut_subcommand(&name, |subcommand| {
+            translate_command(subcommand, &path, catalogue, usage)
+        });
+    }
+
+    command
+}
+
+/// Translates the `help` subcommands clap adds to every command with
+/// subcommands of its own, which mirror the rest.
+fn translate_help_subcommands(
+    mut command: Command,
+    catalogue: &'static Catalogue,
+) -> Command {
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+
+    for name in subcommands {
+        command = command.mut_subcommand(&name, |subcommand| {
+            let subcommand = translate_help_subcommands(subcommand, catalogue);
+
+            match catalogue.help("help") {
+                Some(about) if name == "help" => subcommand.about(about),
+                _ => subcommand,
+            }
+        });
+    }
+
+    command
+}
+
+fn translate_argument(
+    mut arg: Arg,
+    path: &str,
+    catalogue: &'static Catalogue,
+) -> Arg {
+    let id = arg.get_id().as_str();
+
+    let help = if path.is_empty() {
+        catalogue.help(id)
+    } else {
+        catalogue.help(&format!("{path}.{id}"))
+    };
+
+    if let Some(help) = help {
+        arg = arg.help(help);
+    }
+
+    let heading = if arg.is_positional() {
+        catalogue.text("help_arguments")
+    } else {
+        catalogue.text("help_options")
+    };
+    arg = arg.help_heading(heading);
+
+    // Defaults go first, as clap itself puts them before possible values.
+    arg = translate_default(arg, catalogue);
+    arg = translate_values::<ColorWhen>(arg, catalogue);
+    arg = translate_values::<ListFormat>(arg, catalogue);
+    arg = translate_values::<ReportPeriod>(arg, catalogue);
+    arg = translate_values::<SortTasks>(arg, catalogue);
+    translate_values::<ToggleState>(arg, catalogue)
+}
+
+/// Replaces the parser of an argument accepting values of the given kind
+/// with one that also takes their translated names, and lists them in its
+/// help text.
+fn translate_values<E: TranslatedValue>(
+    arg: Arg,
+    catalogue: &'static Catalogue,
+) -> Arg {
+    let names: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect();
+
+    let parser = TranslatedValueParser::<E>::new(catalogue);
+
+    if names.is_empty()
+        || !names
+            .iter()
+            .eq(parser.values.iter().map(PossibleValue::get_name))
+    {
+        return arg;
+    }
+
+    let help = arg.get_help().map(ToString::to_string).unwrap_or_default();
+    let heading = catalogue.text("help_possible_values");
+
+    let short =
+        format!("{help} [{}: {}]", heading.to_lowercase(), names.join(", "));
+
+    let mut long = format!("{help}\n\n{heading}:");
+    let width = names.iter().map(String::len).max().unwrap_or_default();
+
+    for value in &parser.values {
+        let name = value.get_name();
+        let _ = write!(long, "\n- {name}");
+
+        if let Some(help) = value.get_help() {
+            let padding = " ".repeat(width - name.len());
+            let _ = write!(long, ":{padding} {help}");
+        }
+
+        let aliases = value.get_name_and_aliases().skip(1).join(", ");
+
+        if !aliases.is_empty() {
+            let _ = write!(
+                long,
+                " [{}: {aliases}]",
+                catalogue.text("help_aliases")
+            );
+        }
+    }
+
+    arg.value_parser(parser)
+        .hide_possible_values(true)
+        .help(short)
+        .long_help(long)
+}
+
+/// Moves the default value of an argument into its help text, where it can
+/// be labelled in the configured language.
+fn translate_default(arg: Arg, catalogue: &'static Catalogue) -> Arg {
+    let defaults = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy())
+        .join(", ");
+
+    if defaults.is_empty()
+        || arg.is_hide_default_value_set()
+        || !arg.get_action().takes_values()
+    {
+        return arg;
+    }
+
+    let label = catalogue.text("help_default");
+    let help = arg.get_help().map(ToString::to_string).unwrap_or_default();
+    let long = arg.get_long_help().map(ToString::to_string);
+
+    let arg = arg
+        .hide_default_value(true)
+        .help(format!("{help} [{label}: {defaults}]"));
+
+    match long {
+        Some(long) => arg.long_help(format!("{long}\n\n[{label}: {defaults}]")),
+        None => arg,
+    }
+}
+
+/// Parses values of the given kind by their names, their aliases or the
+/// names they have in the configured language.
+#[derive(Clone)]
+pub struct TranslatedValueParser<E> {
+    values: Vec<PossibleValue>,
+    kind: PhantomData<E>,
+}
+
+impl<E: TranslatedValue> TranslatedValueParser<E> {
+    fn new(catalogue: &'static Catalogue) -> Self {
+        let values = E::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| {
+                let Some(text) = catalogue.value(&format!(
+                    "{}.{}",
+                    E::KEY,
+                    value.get_name()
+                )) else {
+                    return value;
+                };
+
+                let value = match &text.help {
+                    Some(help) => value.help(help.as_str()),
+                    None => value,
+                };
+
+                value.aliases(text.aliases.iter().map(String::as_str))
+            })
+            .collect();
+
+        Self {
+            values,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<E: TranslatedValue> TypedValueParser for TranslatedValueParser<E> {
+    type Value = E;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let ignore_case = arg.is_some_and(Arg::is_ignore_case_set);
+
+        value
+            .to_str()
+            .and_then(|value| {
+                self.values
+                    .iter()
+                    .position(|possible| possible.matches(value, ignore_case))
+            })
+            .and_then(|index| E::value_variants().get(index).cloned())
+            .ok_or_else(|| {
+                let mut err =
+                    clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
+
+                if let Some(arg) = arg {
+                    err.insert(
+                        ContextKind::InvalidArg,
+                        ContextValue::String(arg.to_string()),
+                    );
+                }
+
+                err.insert(
+                    ContextKind::InvalidValue,
+                    ContextValue::String(value.to_string_lossy().into_owned()),
+                );
+                err.insert(
+                    ContextKind::ValidValue,
+                    ContextValue::Strings(
+                        self.values
+                            .iter()
+                            .map(|value| value.get_name().to_string())
+                            .collect(),
+                    ),
+                );
+
+                err
+            })
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(self.values.iter().cloned()))
+    }
+}
+
+/// Prints an error for a subcommand that is neither built in nor a report in
+/// the configuration, and exits.
+///
+/// The closest of either is suggested. The path names the subcommand it was
+/// given to, like `report`, if any.
+pub fn exit_unknown_subcommand(
+    path: &[&str],
+    name: &str,
+    config: &Configuration,
+) -> ! {
+    let language = config.language();
+    let mut command = localized_command(language, &config.theme);
+
+    if let Some(binary) = std::env::args_os()
+        .next()
+        .as_deref()
+        .map(Path::new)
+        .and_then(Path::file_name)
+    {
+        command.set_bin_name(binary.to_string_lossy());
+    }
+
+    command.build();
+
+    let mut parent = &command;
+
+    for segment in path {
+        if let Some(subcommand) = parent.find_subcommand(segment) {
+            parent = subcommand;
+        }
+    }
+
+    let mut parent = parent.clone();
+    let suggestion = closest(
+        name,
+        parent
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .flat_map(|subcommand| {
+                std::iter::once(subcommand.get_name())
+                    .chain(subcommand.get_all_aliases())
+            })
+            .chain(config.reports.keys().map(String::as_str)),
+    );
+
+    let mut err =
+        clap::Error::new(ErrorKind::InvalidSubcommand).with_cmd(&parent);
+    err.insert(
+        ContextKind::InvalidSubcommand,
+        ContextValue::String(name.to_string()),
+    );
+
+    if let Some(suggestion) = suggestion {
+        err.insert(
+            ContextKind::SuggestedSubcommand,
+            ContextValue::String(suggestion),
+        );
+    }
+
+    err.insert(
+        ContextKind::Usage,
+        ContextValue::StyledStr(parent.render_usage()),
+    );
+
+    exit_with_error(&err, language, &config.theme)
+}
+
+/// Prints a parsing error in the given language and theme and exits. Errors
+/// without a translation, and requests for help, are left to clap.
+fn exit_with_error(err: &clap::Error, language: Language, theme: &Theme) -> ! {
+    let catalogue = catalogue(language);
+
+    let message = match err.kind() {
+        _ if language == Language::English => None,
+        ErrorKind::DisplayHelp
+        | ErrorKind::DisplayVersion
+        | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => None,
+        kind => error_message(err, kind, catalogue),
+    };
+
+    let Some(message) = message else {
+        err.exit();
+    };
+
+    let mut output = format!(
+        "{}: {message}\n",
+        catalogue.text("error").style(theme.error().bold())
+    );
+
+    if let Some(suggestion) = err.get(ContextKind::SuggestedSubcommand) {
+        let _ = write!(
+            output,
+            "\n  {}\n",
+            catalogue.format(
+                "error_similar_subcommand",
+                &[("suggestion", &suggestion.to_string())]
+            )
+        );
+    }
+
+    if let Some(usage) = err.get(ContextKind::Usage) {
+        let usage = usage.to_string();
+        let usage = usage
+            .split_once(' ')
+            .map_or(usage.as_str(), |(_, usage)| usage);
+
+        let _ = write!(
+            output,
+            "\n{} {usage}\n",
+            catalogue.text("help_usage").style(theme.heading())
+        );
+    }
+
+    let _ = write!(output, "\n{}\n", catalogue.text("error_tip"));
+
+    anstream::eprint!("{output}");
+    std::process::exit(err.exit_code());
+}
+
+fn error_message(
+    err: &clap::Error,
+    kind: ErrorKind,
+    catalogue: &'static Catalogue,
+) -> Option<String> {
+    let context = |kind| err.get(kind).map(ToString::to_string);
+
+    let message = match kind {
+        ErrorKind::InvalidValue => {
+            let value = context(ContextKind::InvalidValue)?;
+            let argument = context(ContextKind::InvalidArg)?;
+
+            if value.is_empty() {
+                catalogue
+                    .format("error_missing_value", &[("argument", &argument)])
+            } else {
+                catalogue.format(
+                    "error_invalid_value",
+                    &[
+                        ("value", &value),
+                        ("argument", &argument),
+                        (
+                            "expected",
+                            &context(ContextKind::ValidValue)
+                                .unwrap_or_default(),
+                        ),
+                    ],
+                )
+            }
+        }
+        ErrorKind::ValueValidation => catalogue.format(
+            "error_invalid_argument",
+            &[
+                ("value", &context(ContextKind::InvalidValue)?),
+                ("argument", &context(ContextKind::InvalidArg)?),
+                (
+                    "reason",
+                    &err.source().map(ToString::to_string).unwrap_or_default(),
+                ),
+            ],
+        ),
+        ErrorKind::UnknownArgument => catalogue.format(
+            "error_unknown_argument",
+            &[("argument", &context(ContextKind::InvalidArg)?)],
+        ),
+        ErrorKind::InvalidSubcommand => catalogue.format(
+            "error_unknown_subcommand",
+            &[("subcommand", &context(ContextKind::InvalidSubcommand)?)],
+        ),
+        ErrorKind::MissingRequiredArgument => catalogue.format(
+            "error_missing_argument",
+            &[("arguments", &context(ContextKind::InvalidArg)?)],
+        ),
+        ErrorKind::ArgumentConflict => catalogue.format(
+            "error_conflict",
+            &[
+                ("argument", &context(ContextKind::InvalidArg)?),
+                ("other", &context(ContextKind::PriorArg)?),
+            ],
+        ),
+        _ => return None,
+    };
+
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LANGUAGES: [Language; 5] = [
+        Language::English,
+        Language::Spanish,
+        Language::Portuguese,
+        Language::French,
+        Language::German,
+    ];
+
+    fn config_file(args: &[&str]) -> Option<String> {
+        let args: Vec<OsString> = std::iter::once(&"tasker-cli")
+            .chain(args)
+            .map(OsString::from)
+            .collect();
+
+        flag_argument(&args, "-C", "--config-file")
+    }
+
+    fn parse<E: TranslatedValue>(
+        language: Language,
+        value: &str,
+    ) -> Result<E, clap::Error> {
+        TranslatedValueParser::<E>::new(catalogue(language)).parse_ref(
+            &Command::new("tasker-cli"),
+            None,
+            OsStr::new(value),
+        )
+    }
+
+    #[test]
+    fn finds_flag_arguments_before_parsing() {
+        assert_eq!(config_file(&["-C", "x"]), Some("x".to_string()));
+        assert_eq!(config_file(&["-Cx"]), Some("x".to_string()));
+        assert_eq!(
+            config_file(&["list", "--config-file=x"]),
+            Some("x".to_string())
+        );
+        assert_eq!(config_file(&["--config-file", "x"]), Some("x".to_string()));
+        assert_eq!(config_file(&["add", "--", "-C", "x"]), None);
+        assert_eq!(config_file(&["-C"]), None);
+        assert_eq!(config_file(&["list"]), None);
+    }
+
+    #[test]
+    fn parses_english_names_in_every_language() {
+        for language in LANGUAGES {
+            assert_eq!(
+                parse::<ToggleState>(language, "doing").ok(),
+                Some(ToggleState::Doing)
+            );
+        }
+    }
+
+    #[test]
+    fn parses_aliases_of_the_catalogue() {
+        assert_eq!(
+            parse::<ToggleState>(Language::Spanish, "hecha").ok(),
+            Some(ToggleState::Done)
+        );
+        assert!(matches!(
+            parse::<SortTasks>(Language::Spanish, "proyecto"),
+            Ok(SortTasks::Project)
+        ));
+        assert!(matches!(
+            parse::<SortTasks>(Language::Spanish, "desc"),
+            Ok(SortTasks::Description)
+        ));
+
+        let err = parse::<ToggleState>(Language::English, "hecha")
+            .expect_err("Spanish names are only taken in Spanish");
+
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn builds_the_command_in_every_language() {
+        for language in LANGUAGES {
+            localized_command(language, &Theme::default()).debug_assert();
+        }
+    }
+
+    #[test]
+    fn converts_theme_styles_for_clap() {
+        assert_eq!(
+            clap_style(owo_colors::Style::new().purple().bold()),
+            styling::Style::new()
+                .fg_color(Some(AnsiColor::Magenta.into()))
+                .bold()
+        );
+        assert_eq!(
+            clap_style(owo_colors::Style::new().on_truecolor(255, 136, 0)),
+            styling::Style::new().bg_color(Some(RgbColor(255, 136, 0).into()))
+        );
+    }
+}
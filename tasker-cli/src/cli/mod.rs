@@ -1,3 +1,5 @@
 pub mod interface;
+pub mod localize;
 
 pub use interface::*;
+pub use localize::*;
@@ -0,0 +1,350 @@
+use super::Configuration;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::ops::Range;
+use toml_edit::{ImDocument, TableLike};
+
+/// Something wrong with a configuration file, pointing at where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: Utf8PathBuf,
+    /// Line of the problem, starting at 1.
+    pub line: usize,
+    /// Column of the problem, starting at 1.
+    pub column: usize,
+    /// Dotted path of the setting at fault, if the problem is in one.
+    pub key: Option<String>,
+    pub problem: Problem,
+    /// Closest valid name to the one given, for unknown settings and values.
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The file isn't valid TOML.
+    Syntax(String),
+    /// A setting has a value of the wrong type or out of its range.
+    InvalidValue(String),
+    /// A setting Tasker doesn't know about, which is ignored.
+    UnknownKey,
+    /// A setting a local configuration file can't change unless its
+    /// directory is trusted, which is ignored.
+    Untrusted,
+}
+
+/// A setting of a configuration file as written, with where its key and
+/// value are.
+struct Entry {
+    key: String,
+    key_span: Range<usize>,
+    value_span: Range<usize>,
+    /// Empty values are left out when serializing, so they can't be told
+    /// apart from unknown settings.
+    empty: bool,
+}
+
+/// Checks the contents of a configuration file. Returns the unknown settings
+/// it has, which don't stop it from loading, or the first problem that does.
+///
+/// # Errors
+///
+/// Returns a diagnostic if the file isn't valid TOML or if any setting has an
+/// invalid value.
+pub fn check_file(
+    path: &Utf8Path,
+    contents: &str,
+) -> Result<Vec<Diagnostic>, Diagnostic> {
+    let diagnostic = |offset: usize, key, problem, suggestion| {
+        let (line, column) = position(contents, offset);
+
+        Diagnostic {
+            path: path.to_owned(),
+            line,
+            column,
+            key,
+            problem,
+            suggestion,
+        }
+    };
+
+    let document = ImDocument::parse(contents)
+        .map_err(|err| syntax_diagnostic(path, contents, &err))?;
+
+    let mut entries = Vec::new();
+    collect_entries("", document.as_table(), &mut entries);
+
+    let config = toml::from_str::<Configuration>(contents).map_err(|err| {
+        let offset = err.span().map_or(0, |span| span.start);
+        let entry = entries.iter().find(|entry| {
+            entry.key_span.start <= offset && offset < entry.value_span.end
+        });
+
+        diagnostic(
+            entry.map_or(offset, |entry| entry.value_span.start),
+            entry.map(|entry| entry.key.clone()),
+            Problem::InvalidValue(err.message().trim().to_string()),
+            suggest_variant(err.message()),
+        )
+    })?;
+
+    let known: Vec<String> =
+        config.settings().into_iter().map(|(key, _)| key).collect();
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| !entry.empty && !is_known(&entry.key, &known))
+        .map(|entry| {
+            diagnostic(
+                entry.key_span.start,
+                Some(entry.key.clone()),
+                Problem::UnknownKey,
+                closest(&entry.key, known.iter().map(String::as_str)),
+            )
+        })
+        .collect())
+}
+
+/// Points at every setting of a configuration file under the given keys,
+/// which are ignored because the file isn't trusted.
+#[must_use]
+pub fn untrusted_settings(
+    path: &Utf8Path,
+    contents: &str,
+    keys: &[&str],
+) -> Vec<Diagnostic> {
+    let Ok(document) = ImDocument::parse(contents) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    collect_entries("", document.as_table(), &mut entries);
+
+    entries
+        .into_iter()
+        .filter(|entry| {
+            keys.iter().any(|key| {
+                entry.key.strip_prefix(key).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('.')
+                })
+            })
+        })
+        .map(|entry| {
+            let (line, column) = position(contents, entry.key_span.start);
+
+            Diagnostic {
+                path: path.to_owned(),
+                line,
+                column,
+                key: Some(entry.key),
+                problem: Problem::Untrusted,
+                suggestion: None,
+            }
+        })
+        .collect()
+}
+
+/// Points at the place where a configuration file stops being valid TOML.
+#[must_use]
+pub fn syntax_diagnostic(
+    path: &Utf8Path,
+    contents: &str,
+    err: &toml_edit::TomlError,
+) -> Diagnostic {
+    let (line, column) =
+        position(contents, err.span().map_or(0, |span| span.start));
+
+    Diagnostic {
+        path: path.to_owned(),
+        line,
+        column,
+        key: None,
+        problem: Problem::Syntax(err.message().trim().to_string()),
+        suggestion: None,
+    }
+}
+
+fn is_known(key: &str, known: &[String]) -> bool {
+    known.iter().any(|name| {
+        name == key
+            || name
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Collects every setting with a value, walking into tables.
+fn collect_entries(
+    prefix: &str,
+    table: &dyn TableLike,
+    entries: &mut Vec<Entry>,
+) {
+    for (name, item) in table.iter() {
+        let key = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix}.{name}")
+        };
+
+        if let Some(inner) = item.as_table_like() {
+            collect_entries(&key, inner, entries);
+            continue;
+        }
+
+        let key_span =
+            table.get_key_value(name).and_then(|(key, _)| key.span());
+
+        if let (Some(key_span), Some(value_span)) = (key_span, item.span()) {
+            entries.push(Entry {
+                key,
+                key_span,
+                value_span,
+                empty: item.as_str().is_some_and(str::is_empty)
+                    || item.as_array().is_some_and(toml_edit::Array::is_empty),
+            });
+        }
+    }
+}
+
+/// Returns the line and column of a byte offset, both starting at 1.
+fn position(contents: &str, offset: usize) -> (usize, usize) {
+    let before = contents.get(..offset).unwrap_or(contents);
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Suggests one of the expected values of a deserialization error like
+/// ``unknown variant `tabel`, expected one of `blocks`, `table` ``.
+fn suggest_variant(message: &str) -> Option<String> {
+    let (given, expected) = message
+        .strip_prefix("unknown variant `")?
+        .split_once("`, expected ")?;
+
+    closest(given, expected.split('`').skip(1).step_by(2))
+}
+
+/// Returns the candidate closest to the given name, if it's close enough to
+/// be a typo of it. Swapped letters count as a single typo.
+pub fn closest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let threshold = (name.chars().count() / 3).max(2);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (strsim::osa_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= threshold)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(contents: &str) -> Result<Vec<Diagnostic>, Diagnostic> {
+        check_file(Utf8Path::new("tasker-cli.toml"), contents)
+    }
+
+    #[test]
+    fn counts_lines_and_columns_from_one() {
+        assert_eq!(position("name = 1", 0), (1, 1));
+        assert_eq!(position("a = 1\nb = 2", 6), (2, 1));
+        assert_eq!(position("a = 1\nb = 2", 10), (2, 5));
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        assert_eq!(position("name = \"Añá\"", 13), (1, 12));
+    }
+
+    #[test]
+    fn points_at_invalid_values() {
+        let diagnostic = check("name = \"A\"\n\n[pomodoro]\nwork = \"long\"\n")
+            .expect_err("the value should be invalid");
+
+        assert_eq!(diagnostic.key.as_deref(), Some("pomodoro.work"));
+        assert_eq!((diagnostic.line, diagnostic.column), (4, 8));
+        assert!(matches!(diagnostic.problem, Problem::InvalidValue(_)));
+    }
+
+    #[test]
+    fn points_at_syntax_errors() {
+        let diagnostic = check("name = \"A\"\nlanguage = \n")
+            .expect_err("the file shouldn't be valid TOML");
+
+        assert_eq!(diagnostic.key, None);
+        assert_eq!(diagnostic.line, 2);
+        assert!(matches!(diagnostic.problem, Problem::Syntax(_)));
+    }
+
+    #[test]
+    fn suggests_settings_for_typos() {
+        let unknown =
+            check("naem = \"A\"\nhello = 1\n\n[pomodoro]\nwrok = 50\n")
+                .expect("unknown settings shouldn't stop the file loading");
+
+        let found: Vec<_> = unknown
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.key.as_deref(),
+                    diagnostic.line,
+                    diagnostic.suggestion.as_deref(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                (Some("naem"), 1, Some("name")),
+                (Some("hello"), 2, None),
+                (Some("pomodoro.wrok"), 5, Some("pomodoro.work")),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggests_values_for_typos() {
+        let diagnostic = check("list_format = \"tabel\"\n")
+            .expect_err("the value should be invalid");
+
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("table"));
+    }
+
+    #[test]
+    fn finds_the_closest_candidate() {
+        let commands = ["list", "edit", "add", "delete"];
+
+        assert_eq!(closest("lsit", commands).as_deref(), Some("list"));
+        assert_eq!(closest("dleete", commands).as_deref(), Some("delete"));
+        assert_eq!(closest("board", commands), None);
+    }
+
+    #[test]
+    fn points_at_untrusted_settings() {
+        let contents = "to_do_path = \"/tmp/todo.ron\"\nto_do_paths = 1\n\n\
+                        [pomodoro]\nwork = 50\nnotify_command = \"true\"\n";
+
+        let keys: Vec<_> = untrusted_settings(
+            Utf8Path::new(".tasker.toml"),
+            contents,
+            &["to_do_path", "pomodoro.notify_command"],
+        )
+        .into_iter()
+        .map(|diagnostic| (diagnostic.key, diagnostic.line))
+        .collect();
+
+        assert_eq!(
+            keys,
+            [
+                (Some("to_do_path".to_string()), 1),
+                (Some("pomodoro.notify_command".to_string()), 6),
+            ]
+        );
+    }
+}
@@ -0,0 +1,780 @@
+use super::{
+    check_file, default_to_do_path, untrusted_settings, Configuration,
+    Diagnostic, Language, Theme, DEFAULT_WORKSPACE, WORKSPACE_SETTINGS,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use lib_tasker::{error::TaskerFailure, todos::ToDo};
+use std::collections::BTreeMap;
+
+/// Name of the project-local configuration file, looked for in the current
+/// directory and its parents.
+pub const LOCAL_FILE_NAME: &str = ".tasker.toml";
+
+/// Settings a local configuration file can only change if its directory is
+/// trusted, since they run commands or choose which files are written.
+pub const TRUSTED_SETTINGS: [&str; 3] =
+    ["pomodoro.notify_command", "to_do_path", "workspaces"];
+
+/// Prefix of the environment variables that override settings.
+const ENVIRONMENT_PREFIX: &str = "TASKER_";
+
+/// Where the value of a setting came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Built into Tasker.
+    Default,
+    /// The configuration file shared by every user of the system.
+    System(Utf8PathBuf),
+    /// The configuration file of the current user, or the one given with
+    /// `--config-file` instead.
+    User(Utf8PathBuf),
+    /// A configuration file in the current directory or one of its parents.
+    Local(Utf8PathBuf),
+    /// An environment variable with the given name.
+    Environment(String),
+    /// A `.tasker.ron` file or `.tasker` directory found in the current
+    /// directory or one of its parents.
+    Discovered,
+    /// The workspace with the given name, overriding the top-level settings.
+    Workspace(String),
+    /// A command line flag with the given name.
+    Flag(&'static str),
+}
+
+/// Reasons the configuration couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    Failure(TaskerFailure),
+    /// A configuration file has a problem that stops it from loading.
+    Invalid(Diagnostic),
+    /// The selected workspace isn't in the configuration.
+    UnknownWorkspace(String),
+    /// An environment variable sets a setting to an invalid value.
+    InvalidVariable {
+        variable: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl<T: Into<TaskerFailure>> From<T> for LoadError {
+    fn from(value: T) -> Self {
+        Self::Failure(value.into())
+    }
+}
+
+impl Origin {
+    /// Returns the path of the configuration file this is, if any.
+    #[must_use]
+    pub fn path(&self) -> Option<&Utf8Path> {
+        match self {
+            Self::System(path) | Self::User(path) | Self::Local(path) => {
+                Some(path)
+            }
+            Self::Default
+            | Self::Environment(_)
+            | Self::Discovered
+            | Self::Workspace(_)
+            | Self::Flag(_) => None,
+        }
+    }
+}
+
+impl Configuration {
+    /// Loads the configuration by layering, from lowest to highest priority,
+    /// the built-in defaults, the system configuration file, the user one or
+    /// the given one instead, a local `.tasker.toml`, `TASKER_*` environment
+    /// variables, the settings of the given workspace or the configured one,
+    /// and the given Tasks file. The user configuration file is created if it
+    /// doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it fails to determine the default paths, if the
+    /// given configuration file doesn't exist, if any file can't be read or
+    /// has an invalid setting, if an environment variable gives one an invalid
+    /// value or if the workspace doesn't exist.
+    pub fn load(
+        config_file: Option<&Utf8Path>,
+        to_do_file: Option<&Utf8Path>,
+        workspace: Option<&str>,
+    ) -> Result<Self, LoadError> {
+        let default_to_do_path = ToDo::get_default_to_do_path()?;
+
+        match config_file {
+            Some(path) => {
+                if !path.try_exists()? {
+                    return Err(LoadError::Failure(
+                        TaskerFailure::ProjectDirectoryError(
+                            std::io::Error::from(std::io::ErrorKind::NotFound),
+                        ),
+                    ));
+                }
+            }
+            None => {
+                if !Self::get_default_path()?.try_exists()? {
+                    Self::defaults(to_do_file.unwrap_or(&default_to_do_path))
+                        .save_config()?;
+                }
+            }
+        }
+
+        let mut merged =
+            toml::Table::try_from(Self::defaults(&default_to_do_path))?;
+        let mut origins = BTreeMap::new();
+        record_origins("", &merged, &Origin::Default, &mut origins);
+
+        let mut warnings = Vec::new();
+
+        for origin in file_layers(config_file)? {
+            if let Some(path) = origin.path() {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(TaskerFailure::from)?;
+                warnings.extend(
+                    check_file(path, &contents).map_err(LoadError::Invalid)?,
+                );
+
+                let mut table: toml::Table = toml::from_str(&contents)?;
+
+                if let Origin::Local(path) = &origin {
+                    let restricted = local_restrictions(&merged, path);
+
+                    warnings.extend(untrusted_settings(
+                        path,
+                        &contents,
+                        &restricted,
+                    ));
+
+                    for key in restricted {
+                        remove(&mut table, key);
+                    }
+                }
+
+                record_origins("", &table, &origin, &mut origins);
+                merge(&mut merged, table);
+            }
+        }
+
+        apply_environment(&mut merged, &mut origins)?;
+
+        if let Some(name) = workspace {
+            origins
+                .insert("workspace".to_string(), Origin::Flag("--workspace"));
+            merged.insert(
+                "workspace".to_string(),
+                toml::Value::String(name.to_string()),
+            );
+        }
+
+        let default_workspace_path = merged
+            .get("to_do_path")
+            .and_then(toml::Value::as_str)
+            .map_or(default_to_do_path, Utf8PathBuf::from);
+
+        apply_workspace(&mut merged, &mut origins)?;
+
+        // A Tasks file kept with the project is more specific than the ones
+        // set for the whole system or user, but not than the rest.
+        if matches!(
+            origins.get("to_do_path"),
+            None | Some(Origin::Default | Origin::System(_) | Origin::User(_))
+        ) {
+            if let Some(path) = discovered_to_do_path() {
+                origins.insert("to_do_path".to_string(), Origin::Discovered);
+                merged.insert(
+                    "to_do_path".to_string(),
+                    toml::Value::String(path.to_string()),
+                );
+            }
+        }
+
+        if let Some(path) = to_do_file {
+            origins
+                .insert("to_do_path".to_string(), Origin::Flag("--todo-file"));
+            merged.insert(
+                "to_do_path".to_string(),
+                toml::Value::String(path.to_string()),
+            );
+        }
+
+        let mut config: Self = merged.try_into()?;
+        config.default_workspace_path = default_workspace_path;
+        config.origins = origins;
+        config.warnings = warnings;
+
+        Ok(config)
+    }
+
+    /// Returns the configuration files that are layered, from lowest to
+    /// highest priority.
+    #[must_use]
+    pub fn files(config_file: Option<&Utf8Path>) -> Vec<Origin> {
+        file_layers(config_file).unwrap_or_default()
+    }
+
+    /// Returns the built-in configuration in the given language, to keep
+    /// going when the configuration files can't be loaded.
+    #[must_use]
+    pub fn fallback(language: Language) -> Self {
+        let mut config = Self::defaults(&default_to_do_path());
+        config.language = Some(language);
+
+        config
+    }
+
+    /// Returns where the current value of the given setting came from.
+    #[must_use]
+    pub fn origin(&self, key: &str) -> Origin {
+        self.origins.get(key).cloned().unwrap_or(Origin::Default)
+    }
+
+    /// Returns the language set by the configuration files, the environment
+    /// or the given workspace or the configured one, before the rest of the
+    /// configuration can be loaded. Falls back to the system locale if none
+    /// of them sets it, ignoring any file that can't be read.
+    #[must_use]
+    pub fn peek_language(
+        config_file: Option<&Utf8Path>,
+        workspace: Option<&str>,
+    ) -> Language {
+        peek(config_file, workspace)
+            .remove("language")
+            .and_then(|language| language.try_into().ok())
+            .unwrap_or_else(Language::from_system)
+    }
+
+    /// Returns the theme set by the configuration files, the environment or
+    /// the given workspace or the configured one, before the rest of the
+    /// configuration can be loaded. Falls back to the default one if it isn't
+    /// valid.
+    #[must_use]
+    pub fn peek_theme(
+        config_file: Option<&Utf8Path>,
+        workspace: Option<&str>,
+    ) -> Theme {
+        peek(config_file, workspace)
+            .remove("theme")
+            .and_then(|theme| theme.try_into().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Merges the configuration files, the environment and the settings of the
+/// given workspace or the configured one as well as possible, ignoring any
+/// file that can't be read and any workspace that doesn't exist.
+fn peek(
+    config_file: Option<&Utf8Path>,
+    workspace: Option<&str>,
+) -> toml::Table {
+    let mut merged = toml::Table::new();
+
+    for origin in file_layers(config_file).unwrap_or_default() {
+        if let Some(Ok(mut table)) = origin.path().map(read_table) {
+            if let Origin::Local(path) = &origin {
+                for key in local_restrictions(&merged, path) {
+                    remove(&mut table, key);
+                }
+            }
+
+            merge(&mut merged, table);
+        }
+    }
+
+    for (_, key, value) in environment_layer(&merged, std::env::vars()) {
+        insert(&mut merged, &key, value);
+    }
+
+    if let Some(name) = workspace {
+        merged.insert(
+            "workspace".to_string(),
+            toml::Value::String(name.to_string()),
+        );
+    }
+
+    // An unknown workspace is reported once the configuration is loaded.
+    let _ = apply_workspace(&mut merged, &mut BTreeMap::new());
+
+    merged
+}
+
+/// Layers the `TASKER_*` environment variables on top of the settings so
+/// far. Each variable is checked on its own so an invalid one can be named,
+/// as long as the settings it's layered on are valid themselves.
+fn apply_environment(
+    merged: &mut toml::Table,
+    origins: &mut BTreeMap<String, Origin>,
+) -> Result<(), LoadError> {
+    let mut valid = merged.clone().try_into::<Configuration>().is_ok();
+
+    for (variable, key, value) in environment_layer(merged, std::env::vars()) {
+        insert(merged, &key, value);
+
+        if valid {
+            if let Err(err) = merged.clone().try_into::<Configuration>() {
+                return Err(LoadError::InvalidVariable {
+                    value: std::env::var(&variable).unwrap_or_default(),
+                    variable,
+                    reason: err.message().to_string(),
+                });
+            }
+        }
+
+        origins.insert(key.join("."), Origin::Environment(variable));
+        valid = true;
+    }
+
+    Ok(())
+}
+
+/// Layers the settings of the selected workspace on top of the top-level
+/// ones, unless it's the default one.
+fn apply_workspace(
+    merged: &mut toml::Table,
+    origins: &mut BTreeMap<String, Origin>,
+) -> Result<(), LoadError> {
+    let Some(name) = merged
+        .get("workspace")
+        .and_then(toml::Value::as_str)
+        .filter(|name| *name != DEFAULT_WORKSPACE)
+        .map(ToString::to_string)
+    else {
+        return Ok(());
+    };
+
+    let settings = lookup(merged, &["workspaces".to_string(), name.clone()])
+        .and_then(toml::Value::as_table)
+        .ok_or_else(|| LoadError::UnknownWorkspace(name.clone()))?;
+
+    let overlay: toml::Table = settings
+        .iter()
+        .filter(|(key, _)| WORKSPACE_SETTINGS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    record_origins("", &overlay, &Origin::Workspace(name), origins);
+    merge(merged, overlay);
+
+    Ok(())
+}
+
+/// Returns the settings the given local configuration file can't change:
+/// the trusted directories themselves, and every trusted setting unless the
+/// file is within one of them.
+fn local_restrictions(
+    merged: &toml::Table,
+    local: &Utf8Path,
+) -> Vec<&'static str> {
+    let trusted = local.parent().is_some_and(|directory| {
+        merged
+            .get("trusted_directories")
+            .and_then(toml::Value::as_array)
+            .is_some_and(|trusted| {
+                trusted
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .any(|trusted| directory.starts_with(trusted))
+            })
+    });
+
+    let mut restricted = vec!["trusted_directories"];
+
+    if !trusted {
+        restricted.extend(TRUSTED_SETTINGS);
+    }
+
+    restricted
+}
+
+/// Returns the configuration files to layer, from lowest to highest
+/// priority, leaving out the ones that don't exist.
+fn file_layers(
+    config_file: Option<&Utf8Path>,
+) -> Result<Vec<Origin>, TaskerFailure> {
+    let user = match config_file {
+        Some(path) => path.to_owned(),
+        None => Configuration::get_default_path()?,
+    };
+
+    let mut layers = Vec::new();
+
+    if let Some(system) = system_path().filter(|path| path.is_file()) {
+        layers.push(Origin::System(system));
+    }
+
+    if user.is_file() {
+        layers.push(Origin::User(user.clone()));
+    }
+
+    if let Some(local) = local_path().filter(|path| *path != user) {
+        layers.push(Origin::Local(local));
+    }
+
+    Ok(layers)
+}
+
+/// Returns the path of the configuration file shared by every user.
+fn system_path() -> Option<Utf8PathBuf> {
+    let directory = if cfg!(windows) {
+        Utf8PathBuf::from(std::env::var("ProgramData").ok()?)
+    } else {
+        Utf8PathBuf::from("/etc")
+    };
+
+    Some(directory.join("tasker").join("tasker-cli.toml"))
+}
+
+/// Returns the closest `.tasker.toml` in the current directory or its
+/// parents.
+fn local_path() -> Option<Utf8PathBuf> {
+    let current = Utf8PathBuf::try_from(std::env::current_dir().ok()?).ok()?;
+
+    current
+        .ancestors()
+        .map(|directory| directory.join(LOCAL_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn discovered_to_do_path() -> Option<Utf8PathBuf> {
+    let current = Utf8PathBuf::try_from(std::env::current_dir().ok()?).ok()?;
+
+    ToDo::discover_to_do_path(&current)
+}
+
+fn read_table(path: &Utf8Path) -> Result<toml::Table, TaskerFailure> {
+    Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Returns the settings overridden by the given `TASKER_*` environment
+/// variables, as the name of the variable, the path of the setting and its
+/// value. Nested settings are separated by a double underscore, as in
+/// `TASKER_POMODORO__WORK`.
+fn environment_layer(
+    base: &toml::Table,
+    variables: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, Vec<String>, toml::Value)> {
+    let mut overrides: Vec<_> = variables
+        .into_iter()
+        .filter_map(|(variable, value)| {
+            let name =
+                variable.strip_prefix(ENVIRONMENT_PREFIX)?.to_lowercase();
+
+            let mut key: Vec<String> =
+                name.split("__").map(ToString::to_string).collect();
+
+            if key.iter().any(String::is_empty) {
+                return None;
+            }
+
+            if key == ["todo_path"] {
+                key = vec!["to_do_path".to_string()];
+            }
+
+            let value = parse_value(lookup(base, &key), &value);
+
+            Some((variable, key, value))
+        })
+        .collect();
+
+    overrides.sort_by(|a, b| a.0.cmp(&b.0));
+
+    overrides
+}
+
+/// Reads the value of an environment variable as TOML, unless the setting
+/// it replaces is text or it isn't valid TOML.
+fn parse_value(current: Option<&toml::Value>, value: &str) -> toml::Value {
+    let text = toml::Value::String(value.to_string());
+
+    if matches!(current, Some(toml::Value::String(_))) {
+        return text;
+    }
+
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or(text)
+}
+
+fn lookup<'a>(
+    table: &'a toml::Table,
+    key: &[String],
+) -> Option<&'a toml::Value> {
+    let (first, rest) = key.split_first()?;
+    let value = table.get(first)?;
+
+    match value {
+        toml::Value::Table(inner) if !rest.is_empty() => lookup(inner, rest),
+        value if rest.is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+fn insert(table: &mut toml::Table, key: &[String], value: toml::Value) {
+    let Some((last, parents)) = key.split_last() else {
+        return;
+    };
+
+    let mut table = table;
+
+    for parent in parents {
+        let entry = table
+            .entry(parent.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+
+        if !entry.is_table() {
+            *entry = toml::Value::Table(toml::Table::new());
+        }
+
+        let toml::Value::Table(inner) = entry else {
+            return;
+        };
+        table = inner;
+    }
+
+    table.insert(last.clone(), value);
+}
+
+/// Removes the setting with the given dotted path, if it's there.
+fn remove(table: &mut toml::Table, key: &str) {
+    let mut segments = key.split('.').collect::<Vec<_>>();
+    let Some(last) = segments.pop() else {
+        return;
+    };
+
+    let mut table = table;
+
+    for segment in segments {
+        let Some(toml::Value::Table(inner)) = table.get_mut(segment) else {
+            return;
+        };
+        table = inner;
+    }
+
+    table.remove(last);
+}
+
+/// Merges a layer on top of the settings so far, replacing any value it sets
+/// and merging the tables both have.
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(inner)), toml::Value::Table(table)) => {
+                merge(inner, table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Records the given origin for every value set by a layer.
+fn record_origins(
+    prefix: &str,
+    table: &toml::Table,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+
+        match value {
+            toml::Value::Table(table) => {
+                record_origins(&key, table, origin, origins);
+            }
+            _ => {
+                origins.insert(key, origin.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(contents: &str) -> toml::Table {
+        toml::from_str(contents).expect("contents should be valid TOML")
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn later_layers_take_precedence() {
+        let mut merged = table(
+            "name = \"System\"\nlanguage = \"German\"\n\n\
+             [pomodoro]\nwork = 25\nshort_break = 5\n",
+        );
+
+        merge(
+            &mut merged,
+            table("name = \"User\"\n\n[pomodoro]\nwork = 50\n"),
+        );
+        merge(&mut merged, table("name = \"Local\"\n"));
+
+        assert_eq!(
+            merged,
+            table(
+                "name = \"Local\"\nlanguage = \"German\"\n\n\
+                 [pomodoro]\nwork = 50\nshort_break = 5\n"
+            )
+        );
+    }
+
+    #[test]
+    fn records_the_origin_of_each_value() {
+        let mut origins = BTreeMap::new();
+        let path = Utf8PathBuf::from("/etc/tasker/tasker-cli.toml");
+
+        record_origins(
+            "",
+            &table("name = \"A\"\n\n[pomodoro]\nwork = 50\n"),
+            &Origin::System(path.clone()),
+            &mut origins,
+        );
+        record_origins(
+            "",
+            &table("[pomodoro]\nwork = 30\n"),
+            &Origin::Environment("TASKER_POMODORO__WORK".to_string()),
+            &mut origins,
+        );
+
+        assert_eq!(origins.get("name"), Some(&Origin::System(path)));
+        assert_eq!(
+            origins.get("pomodoro.work"),
+            Some(&Origin::Environment("TASKER_POMODORO__WORK".to_string()))
+        );
+    }
+
+    #[test]
+    fn environment_variables_reach_nested_settings() {
+        let base = table("name = \"A\"\n\n[pomodoro]\nwork = 25\n");
+        let overrides = environment_layer(
+            &base,
+            variables(&[
+                ("TASKER_POMODORO__WORK", "50"),
+                ("TASKER_NAME", "42"),
+                ("TASKER_TODO_PATH", "/tmp/todo.ron"),
+                ("TASKER_POMODORO__", "1"),
+                ("HOME", "/root"),
+            ]),
+        );
+
+        assert_eq!(
+            overrides,
+            [
+                (
+                    "TASKER_NAME".to_string(),
+                    vec!["name".to_string()],
+                    toml::Value::String("42".to_string()),
+                ),
+                (
+                    "TASKER_POMODORO__WORK".to_string(),
+                    vec!["pomodoro".to_string(), "work".to_string()],
+                    toml::Value::Integer(50),
+                ),
+                (
+                    "TASKER_TODO_PATH".to_string(),
+                    vec!["to_do_path".to_string()],
+                    toml::Value::String("/tmp/todo.ron".to_string()),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn environment_variables_are_inserted_into_tables() {
+        let mut merged = table("[pomodoro]\nwork = 25\n");
+
+        for (_, key, value) in environment_layer(
+            &merged,
+            variables(&[
+                ("TASKER_POMODORO__SHORT_BREAK", "3"),
+                ("TASKER_THEME__BASE", "monochrome"),
+            ]),
+        ) {
+            insert(&mut merged, &key, value);
+        }
+
+        assert_eq!(
+            merged,
+            table(
+                "[pomodoro]\nwork = 25\nshort_break = 3\n\n\
+                 [theme]\nbase = \"monochrome\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn untrusted_local_files_cant_change_trusted_settings() {
+        let merged = table("trusted_directories = [\"/home/me/work\"]\n");
+
+        assert_eq!(
+            local_restrictions(
+                &merged,
+                Utf8Path::new("/home/me/work/app/.tasker.toml")
+            ),
+            ["trusted_directories"]
+        );
+        assert_eq!(
+            local_restrictions(
+                &merged,
+                Utf8Path::new("/home/me/workshop/.tasker.toml")
+            ),
+            [
+                "trusted_directories",
+                "pomodoro.notify_command",
+                "to_do_path",
+                "workspaces"
+            ]
+        );
+    }
+
+    #[test]
+    fn removes_nested_settings() {
+        let mut settings = table(
+            "name = \"A\"\n\n[pomodoro]\nwork = 50\nnotify_command = \"true\"\n",
+        );
+
+        remove(&mut settings, "pomodoro.notify_command");
+        remove(&mut settings, "workspaces");
+        remove(&mut settings, "name.first");
+
+        assert_eq!(settings, table("name = \"A\"\n\n[pomodoro]\nwork = 50\n"));
+    }
+
+    #[test]
+    fn selected_workspaces_override_their_settings() {
+        let mut merged = table(
+            "to_do_path = \"/todo.ron\"\nname = \"A\"\nworkspace = \"work\"\n\n\
+             [workspaces.work]\nto_do_path = \"/work.ron\"\nname = \"B\"\n",
+        );
+        let mut origins = BTreeMap::new();
+
+        apply_workspace(&mut merged, &mut origins)
+            .expect("the workspace exists");
+
+        assert_eq!(merged.get("to_do_path"), Some(&"/work.ron".into()));
+        assert_eq!(merged.get("name"), Some(&"A".into()));
+        assert_eq!(
+            origins.get("to_do_path"),
+            Some(&Origin::Workspace("work".to_string()))
+        );
+
+        merged.insert("workspace".to_string(), "school".into());
+
+        assert!(matches!(
+            apply_workspace(&mut merged, &mut origins),
+            Err(LoadError::UnknownWorkspace(name)) if name == "school"
+        ));
+    }
+}
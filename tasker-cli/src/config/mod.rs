@@ -1,72 +1,275 @@
+mod diagnostics;
+mod layers;
+mod report;
+mod settings;
+mod theme;
+mod workspace;
+
+pub use diagnostics::*;
+pub use layers::*;
+pub use report::*;
+pub use settings::*;
+pub use theme::*;
+pub use workspace::*;
+
+use crate::i18n::{catalogue, Arguments};
 use camino::{Utf8Path, Utf8PathBuf};
-use lib_tasker::{error::TaskerFailure, io::get_project_directories};
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use lib_tasker::{
+    error::TaskerFailure, io::get_project_directories, todos::ToDo,
+};
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    num::{NonZeroU64, NonZeroUsize},
+    sync::OnceLock,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Configuration {
+    #[serde(default = "default_name")]
     pub name: String,
-    pub language: Language,
+    /// Language of every message. Follows the system locale if not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(default = "default_to_do_path")]
     pub to_do_path: Utf8PathBuf,
+    #[serde(default)]
+    pub wip_limits: WipLimits,
+    #[serde(default)]
+    pub pomodoro: Pomodoro,
+    #[serde(default)]
+    pub list_format: ListFormat,
+    #[serde(default = "default_table_columns")]
+    pub table_columns: Vec<Column>,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub reports: IndexMap<String, ReportDefinition>,
+    /// Workspace in use, `default` for the top-level settings or one of
+    /// `workspaces`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub workspaces: IndexMap<String, Workspace>,
+    /// Context in use, one of `contexts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Filters narrowing down listings while selected with `context set`, by
+    /// name, like `work = "project:work or +office"`. Kept as written, like
+    /// the filters of reports.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub contexts: IndexMap<String, String>,
+    /// Directories whose local configuration files, and those of their
+    /// subdirectories, may run commands and choose where Tasks are saved.
+    /// Only read from the system and user configuration files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_directories: Vec<Utf8PathBuf>,
+    /// Tasks file of the default workspace, while another one is in use.
+    #[serde(skip)]
+    pub default_workspace_path: Utf8PathBuf,
+    /// Where the value of each setting came from, by its dotted path.
+    #[serde(skip)]
+    pub origins: BTreeMap<String, Origin>,
+    /// Problems found while loading that didn't stop it, such as unknown
+    /// settings.
+    #[serde(skip)]
+    pub warnings: Vec<Diagnostic>,
+}
+
+fn default_name() -> String {
+    "John Doe".to_string()
+}
+
+/// Falls back to a file in the current directory if the default path can't
+/// be determined, so a missing `to_do_path` never stops the configuration
+/// from loading.
+fn default_to_do_path() -> Utf8PathBuf {
+    ToDo::get_default_to_do_path()
+        .unwrap_or_else(|_| Utf8PathBuf::from("todo.ron"))
+}
+
+/// How each Task is laid out when listing them.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ListFormat {
+    /// A block of several lines per Task, with every detail [aliases: b]
+    #[default]
+    #[value(alias = "b")]
+    Blocks,
+
+    /// A single line per Task [aliases: c]
+    #[value(alias = "c")]
+    Compact,
+
+    /// A row per Task, with columns aligned to each other [aliases: t]
+    #[value(alias = "t")]
+    Table,
 }
 
+fn default_table_columns() -> Vec<Column> {
+    vec![
+        Column::Id,
+        Column::State,
+        Column::Project,
+        Column::Tags,
+        Column::Due,
+        Column::Description,
+    ]
+}
+
+/// Maximum number of Tasks each column of the board should hold before being
+/// highlighted as overloaded.
 #[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WipLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_do: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doing: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<usize>,
+}
+
+/// Lengths of each phase of a pomodoro cycle, in minutes.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pomodoro {
+    pub work: NonZeroU64,
+    pub short_break: NonZeroU64,
+    pub long_break: NonZeroU64,
+    /// Number of pomodoros before taking a long break instead of a short one.
+    pub long_break_every: NonZeroUsize,
+    /// Shell command run whenever a phase ends, such as `notify-send Tasker`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_command: Option<String>,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self {
+            work: NonZeroU64::new(25).unwrap_or(NonZeroU64::MIN),
+            short_break: NonZeroU64::new(5).unwrap_or(NonZeroU64::MIN),
+            long_break: NonZeroU64::new(15).unwrap_or(NonZeroU64::MIN),
+            long_break_every: NonZeroUsize::new(4).unwrap_or(NonZeroUsize::MIN),
+            notify_command: None,
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
 pub enum Language {
     #[default]
     English,
     Spanish,
+    Portuguese,
+    French,
+    German,
 }
 
-impl Configuration {
-    /// Returns a new configuration struct.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if it fails to determine the default paths for application
-    /// data, if it fails to deserialize the configuration file or if it fails
-    /// to determine the existence of the configuration path.
-    pub fn new(to_do_path: &Utf8Path) -> Result<Self, TaskerFailure> {
-        let config_path = Self::get_default_path()?;
-
-        match config_path.try_exists() {
-            Ok(true) => {
-                Ok(toml::from_str(&std::fs::read_to_string(config_path)?)?)
-            }
-            Ok(false) => {
-                let config = Self {
-                    name: "John Doe".to_string(),
-                    language: Language::default(),
-                    to_do_path: to_do_path.to_owned(),
-                };
-
-                config.save_config()?;
-
-                Ok(config)
-            }
-            Err(err) => Err(TaskerFailure::ProjectDirectoryError(err)),
+impl Language {
+    /// Returns the language of the system locale, or English if it isn't one
+    /// of the supported ones.
+    #[must_use]
+    pub fn from_system() -> Self {
+        static SYSTEM: OnceLock<Language> = OnceLock::new();
+
+        *SYSTEM.get_or_init(|| {
+            sys_locale::get_locale()
+                .map_or_else(Self::default, |locale| Self::from_locale(&locale))
+        })
+    }
+
+    /// Returns the language of a locale like `pt-BR` or `de_DE.UTF-8`, or
+    /// English if it isn't one of the supported ones.
+    #[must_use]
+    pub fn from_locale(locale: &str) -> Self {
+        let code = locale
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match code.as_str() {
+            "es" => Self::Spanish,
+            "pt" => Self::Portuguese,
+            "fr" => Self::French,
+            "de" => Self::German,
+            _ => Self::English,
         }
     }
+}
 
-    /// Deserializes a configuration struct from the given file path.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the given file path doesn't exist, or if it fails to
-    /// determine its existence.
-    pub fn from_given_file(
-        file_path: &Utf8Path,
-    ) -> Result<Self, TaskerFailure> {
-        match file_path.try_exists() {
-            Ok(true) => {
-                Ok(toml::from_str(&std::fs::read_to_string(file_path)?)?)
-            }
-            Ok(false) => Err(TaskerFailure::ProjectDirectoryError(
-                std::io::Error::from(std::io::ErrorKind::NotFound),
-            )),
-            Err(err) => Err(TaskerFailure::ProjectDirectoryError(err)),
+impl Configuration {
+    /// Returns the built-in configuration, saving Tasks to the given path.
+    fn defaults(to_do_path: &Utf8Path) -> Self {
+        Self {
+            name: default_name(),
+            language: None,
+            to_do_path: to_do_path.to_owned(),
+            wip_limits: WipLimits::default(),
+            pomodoro: Pomodoro::default(),
+            list_format: ListFormat::default(),
+            table_columns: default_table_columns(),
+            theme: Theme::default(),
+            reports: IndexMap::new(),
+            workspace: None,
+            workspaces: IndexMap::new(),
+            context: None,
+            contexts: IndexMap::new(),
+            trusted_directories: Vec::new(),
+            default_workspace_path: to_do_path.to_owned(),
+            origins: BTreeMap::new(),
+            warnings: Vec::new(),
         }
     }
 
+    /// Returns the configured language, or the one of the system locale.
+    #[must_use]
+    pub fn language(&self) -> Language {
+        self.language.unwrap_or_else(Language::from_system)
+    }
+
+    /// Returns the message with the given key in the configured language.
+    #[must_use]
+    pub fn text(&self, key: &'static str) -> &'static str {
+        catalogue(self.language()).text(key)
+    }
+
+    /// Returns the message with the given key in the configured language,
+    /// with its placeholders filled in.
+    #[must_use]
+    pub fn format(&self, key: &'static str, arguments: &Arguments) -> String {
+        catalogue(self.language()).format(key, arguments)
+    }
+
+    /// Returns the form of the message with the given key matching `count` in
+    /// the configured language, with its placeholders filled in.
+    #[must_use]
+    pub fn plural(
+        &self,
+        key: &'static str,
+        count: usize,
+        arguments: &Arguments,
+    ) -> String {
+        catalogue(self.language()).plural(key, count, arguments)
+    }
+
     /// Returns the default configuration path.
     ///
     /// # Errors
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// A named listing of Tasks, run as `tasker report NAME` or simply
+/// `tasker NAME`.
+///
+/// The filter and sort order are kept as written so a mistake in one report
+/// doesn't keep the rest of the configuration from loading.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportDefinition {
+    /// Expression Tasks must match, like `project:work and not state:done`.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub filter: String,
+    /// Fields to sort by in order of priority, like `["state", "id:desc"]`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sort: Vec<String>,
+    pub columns: Vec<Column>,
+    pub group_by: GroupBy,
+    /// Maximum number of Tasks to show, after sorting them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl Default for ReportDefinition {
+    fn default() -> Self {
+        Self {
+            filter: String::new(),
+            sort: Vec::new(),
+            columns: vec![
+                Column::Id,
+                Column::State,
+                Column::Description,
+                Column::Project,
+                Column::Tags,
+            ],
+            group_by: GroupBy::None,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Id,
+    State,
+    Description,
+    Project,
+    Tags,
+    Due,
+    Estimate,
+    Created,
+    Completed,
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    #[default]
+    None,
+    Project,
+    Tag,
+    State,
+}
@@ -0,0 +1,630 @@
+use super::{
+    check_file, syntax_diagnostic, Configuration, Diagnostic, Problem,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::{fs::OpenOptions, io::ErrorKind};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// Reasons a setting couldn't be read or changed.
+#[derive(Debug)]
+pub enum SettingError {
+    /// The configuration file couldn't be read.
+    Io(std::io::Error),
+    /// The configuration file isn't valid TOML.
+    Malformed(Diagnostic),
+    /// There's no setting with the given key.
+    Unknown(String),
+    /// The setting isn't in the configuration file.
+    NotSet(String),
+    /// The configuration wouldn't load after the change, for the given reason.
+    Invalid(String, String),
+    /// Tasks couldn't be saved to the given path.
+    Unwritable(Utf8PathBuf, std::io::Error),
+    /// The setting selects a workspace with the given name, which doesn't
+    /// exist.
+    UnknownWorkspace(String),
+    /// The setting selects a context with the given name, which doesn't
+    /// exist.
+    UnknownContext(String),
+}
+
+impl Configuration {
+    /// Returns every setting with its current value, defaults included, keyed
+    /// by its dotted path and sorted by it.
+    #[must_use]
+    pub fn settings(&self) -> Vec<(String, toml::Value)> {
+        let mut settings = Vec::new();
+
+        if let Ok(toml::Value::Table(mut table)) = toml::Value::try_from(self) {
+            if let Ok(language) = toml::Value::try_from(self.language()) {
+                table.entry("language").or_insert(language);
+            }
+
+            flatten("", table, &mut settings);
+        }
+
+        settings
+    }
+
+    /// Returns the setting with the given key, or every setting under it if
+    /// it names a table.
+    #[must_use]
+    pub fn setting(&self, key: &str) -> Vec<(String, toml::Value)> {
+        self.settings()
+            .into_iter()
+            .filter(|(name, _)| {
+                name.strip_prefix(key).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('.')
+                })
+            })
+            .collect()
+    }
+}
+
+fn flatten(
+    prefix: &str,
+    table: toml::Table,
+    settings: &mut Vec<(String, toml::Value)>,
+) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}.{name}")
+        };
+
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, settings),
+            value => settings.push((key, value)),
+        }
+    }
+}
+
+/// A change made to bring a configuration file up to date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// An unknown setting was renamed to the known one it's a typo of.
+    Renamed(String, String),
+    /// An unknown setting was removed.
+    Removed(String),
+    /// A setting with an invalid value was removed so its default applies,
+    /// for the given reason.
+    Reset(String, String),
+    /// A missing setting was added with its default value.
+    Added(String),
+}
+
+/// The configuration file as it's written, so settings can be changed
+/// without losing its comments or formatting.
+#[derive(Debug)]
+pub struct ConfigDocument {
+    path: Utf8PathBuf,
+    document: DocumentMut,
+}
+
+impl ConfigDocument {
+    /// Reads the configuration file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't valid TOML.
+    pub fn open(path: &Utf8Path) -> Result<Self, SettingError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(SettingError::Io)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            document: contents.parse().map_err(|err| {
+                SettingError::Malformed(syntax_diagnostic(
+                    path, &contents, &err,
+                ))
+            })?,
+        })
+    }
+
+    /// Changes the value of a setting, reading it as TOML if possible and as
+    /// text otherwise. Paths to Tasks files are made absolute from the
+    /// current directory. Returns the configuration as it would load
+    /// afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key doesn't name a setting, if the
+    /// configuration wouldn't load with the new value or, for `to_do_path`,
+    /// if Tasks couldn't be saved there.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+    ) -> Result<Configuration, SettingError> {
+        let segments = split_key(key)?;
+
+        let absolute;
+        let value = if segments.last() == Some(&"to_do_path") {
+            absolute = absolute_path(value)?;
+            absolute.as_str()
+        } else {
+            value
+        };
+
+        let candidates = value
+            .parse::<Value>()
+            .ok()
+            .filter(|parsed| !parsed.is_str())
+            .into_iter()
+            .chain([Value::from(value)]);
+
+        let mut failure = None;
+
+        for mut candidate in candidates {
+            candidate.decor_mut().clear();
+
+            let mut document = self.document.clone();
+
+            if !insert(&mut document, &segments, Item::Value(candidate)) {
+                return Err(SettingError::Unknown(key.to_string()));
+            }
+
+            match load(&document, key) {
+                Ok(config) if config.setting(key).is_empty() => {
+                    return Err(SettingError::Unknown(key.to_string()));
+                }
+                Ok(config) => {
+                    if let Some(err) = missing_reference(&config, key) {
+                        return Err(err);
+                    }
+
+                    if key == "to_do_path" {
+                        check_writable(&config.to_do_path).map_err(|err| {
+                            SettingError::Unwritable(
+                                config.to_do_path.clone(),
+                                err,
+                            )
+                        })?;
+                    }
+
+                    self.document = document;
+                    return Ok(config);
+                }
+                Err(err) => {
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+
+        Err(failure.unwrap_or_else(|| SettingError::Unknown(key.to_string())))
+    }
+
+    /// Removes a setting from the file, so its default applies again.
+    /// Returns the configuration as it would load afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the setting isn't in the file or if the
+    /// configuration wouldn't load without it.
+    pub fn unset(&mut self, key: &str) -> Result<Configuration, SettingError> {
+        let segments = split_key(key)?;
+        let mut document = self.document.clone();
+
+        if take(&mut document, &segments).is_none() {
+            return Err(SettingError::NotSet(key.to_string()));
+        }
+
+        let config = load(&document, key)?;
+        self.document = document;
+
+        Ok(config)
+    }
+
+    /// Brings the file up to date: renames unknown settings that are typos of
+    /// known ones and removes the rest, removes invalid values so their
+    /// defaults apply and adds missing settings with their defaults. Returns
+    /// the changes made, which aren't written until the file is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns the problem if the file has one that can't be repaired, like
+    /// not being valid TOML.
+    pub fn repair(&mut self) -> Result<Vec<Repair>, Diagnostic> {
+        let mut repairs = Vec::new();
+
+        loop {
+            let unknown =
+                match check_file(&self.path, &self.document.to_string()) {
+                    Ok(unknown) => unknown,
+                    Err(diagnostic) => {
+                        let Some(key) = &diagnostic.key else {
+                            return Err(diagnostic);
+                        };
+                        let Problem::InvalidValue(reason) = &diagnostic.problem
+                        else {
+                            return Err(diagnostic);
+                        };
+
+                        if take(&mut self.document, &key_segments(key))
+                            .is_none()
+                        {
+                            return Err(diagnostic);
+                        }
+
+                        repairs
+                            .push(Repair::Reset(key.clone(), reason.clone()));
+                        continue;
+                    }
+                };
+
+            let mut changed = false;
+
+            for diagnostic in unknown {
+                let Some(key) = diagnostic.key else {
+                    continue;
+                };
+                let Some(item) = take(&mut self.document, &key_segments(&key))
+                else {
+                    continue;
+                };
+
+                changed = true;
+
+                match diagnostic.suggestion {
+                    Some(known)
+                        if lookup(&self.document, &key_segments(&known))
+                            .is_none()
+                            && insert(
+                                &mut self.document,
+                                &key_segments(&known),
+                                item,
+                            ) =>
+                    {
+                        repairs.push(Repair::Renamed(key, known));
+                    }
+                    _ => repairs.push(Repair::Removed(key)),
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        repairs.extend(self.add_missing());
+
+        Ok(repairs)
+    }
+
+    /// Adds every top-level setting missing from the file with its default
+    /// value, after the tables already in it.
+    fn add_missing(&mut self) -> Vec<Repair> {
+        let Ok(config) =
+            toml::from_str::<Configuration>(&self.document.to_string())
+        else {
+            return Vec::new();
+        };
+        let Some(defaults) =
+            toml::to_string(&Configuration::defaults(&config.to_do_path))
+                .ok()
+                .and_then(|defaults| defaults.parse::<DocumentMut>().ok())
+        else {
+            return Vec::new();
+        };
+
+        let mut position = self
+            .document
+            .iter()
+            .filter_map(|(_, item)| item.as_table()?.position())
+            .max()
+            .unwrap_or_default();
+        let mut added = Vec::new();
+
+        for (name, item) in defaults.iter() {
+            if self.document.contains_key(name) {
+                continue;
+            }
+
+            let mut item = item.clone();
+
+            if let Item::Table(table) = &mut item {
+                position += 1;
+                table.set_position(position);
+            }
+
+            self.document.insert(name, item);
+            added.push(Repair::Added(name.to_string()));
+        }
+
+        added
+    }
+
+    /// Writes the file back with its changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::write(&self.path, self.document.to_string())
+    }
+}
+
+/// Returns the error for the given setting naming a workspace or context
+/// the configuration doesn't have, if it does.
+fn missing_reference(
+    config: &Configuration,
+    key: &str,
+) -> Option<SettingError> {
+    match key {
+        "workspace" if !config.has_workspace(config.active_workspace()) => {
+            Some(SettingError::UnknownWorkspace(
+                config.active_workspace().to_string(),
+            ))
+        }
+        "context" => config
+            .context
+            .as_ref()
+            .filter(|name| !config.contexts.contains_key(*name))
+            .map(|name| SettingError::UnknownContext(name.clone())),
+        _ => None,
+    }
+}
+
+fn key_segments(key: &str) -> Vec<&str> {
+    key.split('.').collect()
+}
+
+fn lookup<'a>(
+    document: &'a DocumentMut,
+    segments: &[&str],
+) -> Option<&'a Item> {
+    let (last, parents) = segments.split_last()?;
+    let mut table: &dyn TableLike = document.as_table();
+
+    for segment in parents {
+        table = table.get(segment)?.as_table_like()?;
+    }
+
+    table.get(last)
+}
+
+fn split_key(key: &str) -> Result<Vec<&str>, SettingError> {
+    let segments: Vec<&str> = key.split('.').map(str::trim).collect();
+
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(SettingError::Unknown(key.to_string()));
+    }
+
+    Ok(segments)
+}
+
+/// Inserts the item at the given path, creating any missing tables and
+/// keeping the comments around a value it replaces. Returns false if a part
+/// of the path isn't a table.
+fn insert(document: &mut DocumentMut, segments: &[&str], item: Item) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+
+    for segment in parents {
+        match table
+            .entry(segment)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+        {
+            Some(inner) => table = inner,
+            None => return false,
+        }
+    }
+
+    match (table.get_mut(last), item) {
+        (Some(Item::Value(existing)), Item::Value(mut value)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        (Some(existing), item) => *existing = item,
+        (None, item) => {
+            table.insert(last, item);
+        }
+    }
+
+    true
+}
+
+/// Removes the item at the given path and returns it, if it's there.
+fn take(document: &mut DocumentMut, segments: &[&str]) -> Option<Item> {
+    let (last, parents) = segments.split_last()?;
+
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+
+    for segment in parents {
+        table = table.get_mut(segment).and_then(Item::as_table_like_mut)?;
+    }
+
+    table.remove(last)
+}
+
+fn load(
+    document: &DocumentMut,
+    key: &str,
+) -> Result<Configuration, SettingError> {
+    toml::from_str(&document.to_string()).map_err(|err| {
+        SettingError::Invalid(key.to_string(), err.message().to_string())
+    })
+}
+
+/// Returns the given path joined to the current directory, unless it's
+/// already absolute.
+fn absolute_path(path: &str) -> Result<Utf8PathBuf, SettingError> {
+    let path = Utf8Path::new(path);
+
+    if path.is_absolute() {
+        return Ok(path.to_owned());
+    }
+
+    let current = std::env::current_dir().map_err(SettingError::Io)?;
+
+    Utf8PathBuf::try_from(current)
+        .map(|current| current.join(path))
+        .map_err(|err| SettingError::Io(err.into_io_error()))
+}
+
+/// Checks that Tasks could be saved to the given path, without touching an
+/// existing file.
+fn check_writable(path: &Utf8Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "it's a directory",
+        ));
+    }
+
+    if path.exists() {
+        return OpenOptions::new().append(true).open(path).map(drop);
+    }
+
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_str().is_empty())
+        .unwrap_or_else(|| Utf8Path::new("."));
+    let probe = parent.join(format!(".tasker-{}.tmp", std::process::id()));
+
+    std::fs::File::create(&probe)?;
+    std::fs::remove_file(probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(contents: &str) -> ConfigDocument {
+        ConfigDocument {
+            path: Utf8PathBuf::from("tasker-cli.toml"),
+            document: contents.parse().expect("contents should be valid TOML"),
+        }
+    }
+
+    #[test]
+    fn set_reads_values_as_toml() {
+        let mut file = document("name = \"A\"\n");
+        let config = file
+            .set("pomodoro.work", "50")
+            .expect("the value should be valid");
+
+        assert_eq!(config.pomodoro.work.get(), 50);
+        assert_eq!(
+            lookup(&file.document, &["pomodoro", "work"])
+                .and_then(Item::as_integer),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn set_falls_back_to_text() {
+        let mut file = document("name = \"A\"\n");
+        let config = file.set("name", "42").expect("names can be numbers");
+
+        assert_eq!(config.name, "42");
+        assert_eq!(
+            file.document.get("name").and_then(Item::as_str),
+            Some("42")
+        );
+    }
+
+    #[test]
+    fn set_rejects_unknown_settings_and_invalid_values() {
+        let mut file = document("name = \"A\"\n");
+
+        assert!(matches!(
+            file.set("colour", "red"),
+            Err(SettingError::Unknown(key)) if key == "colour"
+        ));
+        assert!(matches!(
+            file.set("pomodoro.work", "0"),
+            Err(SettingError::Invalid(key, _)) if key == "pomodoro.work"
+        ));
+        assert_eq!(file.document.to_string(), "name = \"A\"\n");
+    }
+
+    #[test]
+    fn makes_tasks_paths_absolute() {
+        let path = absolute_path("todo.ron").expect("the path should resolve");
+
+        assert!(path.is_absolute());
+        assert!(path.ends_with("todo.ron"));
+        assert_eq!(
+            absolute_path("/tmp/todo.ron").ok(),
+            Some(Utf8PathBuf::from("/tmp/todo.ron"))
+        );
+    }
+
+    #[test]
+    fn unset_removes_settings() {
+        let mut file = document("name = \"A\"\n\n[pomodoro]\nwork = 50\n");
+        let config = file
+            .unset("pomodoro.work")
+            .expect("the setting should be set");
+
+        assert_eq!(config.pomodoro.work.get(), 25);
+        assert!(lookup(&file.document, &["pomodoro", "work"]).is_none());
+        assert!(matches!(
+            file.unset("pomodoro.work"),
+            Err(SettingError::NotSet(key)) if key == "pomodoro.work"
+        ));
+    }
+
+    #[test]
+    fn repair_renames_typos() {
+        let mut file = document("naem = \"A\"\n\n[pomodoro]\nwrok = 50\n");
+        let repairs = file.repair().expect("the file should be repairable");
+
+        assert!(repairs.contains(&Repair::Renamed(
+            "naem".to_string(),
+            "name".to_string()
+        )));
+        assert!(repairs.contains(&Repair::Renamed(
+            "pomodoro.wrok".to_string(),
+            "pomodoro.work".to_string()
+        )));
+        assert_eq!(file.document.get("name").and_then(Item::as_str), Some("A"));
+        assert_eq!(
+            lookup(&file.document, &["pomodoro", "work"])
+                .and_then(Item::as_integer),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn repair_removes_unknown_settings() {
+        let mut file = document("hello = 1\n");
+        let repairs = file.repair().expect("the file should be repairable");
+
+        assert!(repairs.contains(&Repair::Removed("hello".to_string())));
+        assert!(!file.document.contains_key("hello"));
+    }
+
+    #[test]
+    fn repair_resets_invalid_values() {
+        let mut file =
+            document("name = \"A\"\n\n[pomodoro]\nwork = 0\nshort_break = 3\n");
+        let repairs = file.repair().expect("the file should be repairable");
+
+        assert!(repairs.iter().any(|repair| matches!(
+            repair,
+            Repair::Reset(key, _) if key == "pomodoro.work"
+        )));
+        assert!(lookup(&file.document, &["pomodoro", "work"]).is_none());
+        assert_eq!(
+            lookup(&file.document, &["pomodoro", "short_break"])
+                .and_then(Item::as_integer),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn repair_removes_typos_of_settings_already_set() {
+        let mut file = document("name = \"A\"\nnaem = \"B\"\n");
+        let repairs = file.repair().expect("the file should be repairable");
+
+        assert!(repairs.contains(&Repair::Removed("naem".to_string())));
+        assert_eq!(file.document.get("name").and_then(Item::as_str), Some("A"));
+    }
+}
@@ -0,0 +1,401 @@
+use lib_tasker::todos::State;
+use owo_colors::{AnsiColors, Effect, Style};
+use serde::{Deserialize, Serialize};
+
+/// Colours and styles used to print each kind of element, starting from one
+/// of the built-in themes.
+///
+/// Every element not given here takes the style of the base theme. Styles
+/// are written as words separated by spaces, like `bold red on black` or
+/// `italic #ff8800`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub base: BaseTheme,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_do: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doing: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waiting: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<ThemeStyle>,
+    /// Labels of details such as due dates and estimates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<ThemeStyle>,
+    /// Titles of sections in statistics and reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<ThemeStyle>,
+    /// Bars of charts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chart: Option<ThemeStyle>,
+    /// Text that stands out from its surroundings, like descriptions of
+    /// Tasks shown on their own and totals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emphasis: Option<ThemeStyle>,
+    /// Secondary details, like dates, hints and separators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<ThemeStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ThemeStyle>,
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseTheme {
+    #[default]
+    Default,
+    /// Tells states and messages apart without relying on red and green.
+    ColorBlind,
+    /// Uses text styles alone, for terminals with unreliable colours.
+    Monochrome,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Role {
+    ToDo,
+    Doing,
+    Waiting,
+    Done,
+    Id,
+    Project,
+    Tag,
+    Label,
+    Heading,
+    Chart,
+    Emphasis,
+    Muted,
+    Success,
+    Info,
+    Warning,
+    Error,
+}
+
+impl BaseTheme {
+    const fn style(self, role: Role) -> Style {
+        let style = Style::new();
+
+        match self {
+            Self::Default => match role {
+                Role::ToDo | Role::Info => style.blue(),
+                Role::Doing | Role::Warning => style.yellow(),
+                Role::Waiting | Role::Error => style.red(),
+                Role::Done | Role::Success => style.green(),
+                Role::Id | Role::Project => style.purple(),
+                Role::Tag | Role::Label | Role::Chart => style.cyan(),
+                Role::Heading => style.purple().underline(),
+                Role::Emphasis => style.bold(),
+                Role::Muted => style.dimmed(),
+            },
+            Self::ColorBlind => match role {
+                Role::ToDo | Role::Info | Role::Chart => style.bright_blue(),
+                Role::Doing | Role::Warning => style.yellow(),
+                Role::Waiting => style.magenta().italic(),
+                Role::Done | Role::Success => style.cyan(),
+                Role::Error => style.magenta().bold(),
+                Role::Id | Role::Label | Role::Emphasis => style.bold(),
+                Role::Project => style.blue(),
+                Role::Tag => style.bright_black(),
+                Role::Heading => style.bold().underline(),
+                Role::Muted => style.dimmed(),
+            },
+            Self::Monochrome => match role {
+                Role::ToDo | Role::Label | Role::Info | Role::Chart => style,
+                Role::Doing
+                | Role::Id
+                | Role::Emphasis
+                | Role::Warning
+                | Role::Error => style.bold(),
+                Role::Heading => style.bold().underline(),
+                Role::Waiting | Role::Tag => style.italic(),
+                Role::Done | Role::Muted => style.dimmed(),
+                Role::Project | Role::Success => style.underline(),
+            },
+        }
+    }
+}
+
+impl Theme {
+    fn pick(&self, custom: Option<&ThemeStyle>, role: Role) -> Style {
+        custom.map_or_else(|| self.base.style(role), |custom| custom.style)
+    }
+
+    #[must_use]
+    pub fn state(&self, state: State) -> Style {
+        match state {
+            State::ToDo => self.pick(self.to_do.as_ref(), Role::ToDo),
+            State::Doing => self.pick(self.doing.as_ref(), Role::Doing),
+            State::Waiting => self.pick(self.waiting.as_ref(), Role::Waiting),
+            State::Done => self.pick(self.done.as_ref(), Role::Done),
+        }
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Style {
+        self.pick(self.id.as_ref(), Role::Id)
+    }
+
+    #[must_use]
+    pub fn project(&self) -> Style {
+        self.pick(self.project.as_ref(), Role::Project)
+    }
+
+    #[must_use]
+    pub fn tag(&self) -> Style {
+        self.pick(self.tag.as_ref(), Role::Tag)
+    }
+
+    #[must_use]
+    pub fn label(&self) -> Style {
+        self.pick(self.label.as_ref(), Role::Label)
+    }
+
+    #[must_use]
+    pub fn heading(&self) -> Style {
+        self.pick(self.heading.as_ref(), Role::Heading)
+    }
+
+    #[must_use]
+    pub fn chart(&self) -> Style {
+        self.pick(self.chart.as_ref(), Role::Chart)
+    }
+
+    #[must_use]
+    pub fn emphasis(&self) -> Style {
+        self.pick(self.emphasis.as_ref(), Role::Emphasis)
+    }
+
+    #[must_use]
+    pub fn muted(&self) -> Style {
+        self.pick(self.muted.as_ref(), Role::Muted)
+    }
+
+    #[must_use]
+    pub fn success(&self) -> Style {
+        self.pick(self.success.as_ref(), Role::Success)
+    }
+
+    #[must_use]
+    pub fn info(&self) -> Style {
+        self.pick(self.info.as_ref(), Role::Info)
+    }
+
+    #[must_use]
+    pub fn warning(&self) -> Style {
+        self.pick(self.warning.as_ref(), Role::Warning)
+    }
+
+    #[must_use]
+    pub fn error(&self) -> Style {
+        self.pick(self.error.as_ref(), Role::Error)
+    }
+}
+
+/// Returns the SGR parameters a style starts with, like `[1, 35]` for bold
+/// purple, to translate it into the styles of other libraries.
+#[must_use]
+pub fn sgr_codes(style: Style) -> Vec<u8> {
+    style
+        .prefix_formatter()
+        .to_string()
+        .trim_start_matches("\x1b[")
+        .trim_end_matches('m')
+        .split(';')
+        .filter_map(|code| code.parse().ok())
+        .collect()
+}
+
+/// A style parsed from the configuration file, keeping the text it was
+/// written as to save it back unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThemeStyle {
+    text: String,
+    style: Style,
+}
+
+impl TryFrom<String> for ThemeStyle {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let mut style = Style::new();
+        let mut background = false;
+
+        for word in text.split_whitespace() {
+            let word = word.to_lowercase();
+
+            if word == "on" {
+                background = true;
+                continue;
+            }
+
+            if let Some(effect) = parse_effect(&word) {
+                style = style.effect(effect);
+                continue;
+            }
+
+            style = match (parse_color(&word), background) {
+                (Some(Color::Ansi(color)), false) => style.color(color),
+                (Some(Color::Ansi(color)), true) => style.on_color(color),
+                (Some(Color::Rgb(r, g, b)), false) => style.truecolor(r, g, b),
+                (Some(Color::Rgb(r, g, b)), true) => {
+                    style.on_truecolor(r, g, b)
+                }
+                (None, _) => {
+                    return Err(format!(
+                        "invalid style `{text}`: unknown colour or effect \
+                         `{word}`"
+                    ))
+                }
+            };
+            background = false;
+        }
+
+        if background {
+            return Err(format!(
+                "invalid style `{text}`: expected a colour after `on`"
+            ));
+        }
+
+        Ok(Self { text, style })
+    }
+}
+
+impl From<ThemeStyle> for String {
+    fn from(value: ThemeStyle) -> Self {
+        value.text
+    }
+}
+
+enum Color {
+    Ansi(AnsiColors),
+    Rgb(u8, u8, u8),
+}
+
+fn parse_effect(word: &str) -> Option<Effect> {
+    match word {
+        "bold" => Some(Effect::Bold),
+        "dim" | "dimmed" => Some(Effect::Dimmed),
+        "italic" => Some(Effect::Italic),
+        "underline" => Some(Effect::Underline),
+        "blink" => Some(Effect::Blink),
+        "reversed" => Some(Effect::Reversed),
+        "strikethrough" => Some(Effect::Strikethrough),
+        _ => None,
+    }
+}
+
+fn parse_color(word: &str) -> Option<Color> {
+    if let Some(hex) = word.strip_prefix('#') {
+        let channel = |range| {
+            hex.get(range)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+
+        return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+            (6, Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+            _ => None,
+        };
+    }
+
+    let (bright, name) = word
+        .strip_prefix("bright_")
+        .map_or((false, word), |name| (true, name));
+
+    let color = match (name, bright) {
+        ("black", false) => AnsiColors::Black,
+        ("red", false) => AnsiColors::Red,
+        ("green", false) => AnsiColors::Green,
+        ("yellow", false) => AnsiColors::Yellow,
+        ("blue", false) => AnsiColors::Blue,
+        ("magenta" | "purple", false) => AnsiColors::Magenta,
+        ("cyan", false) => AnsiColors::Cyan,
+        ("white", false) => AnsiColors::White,
+        ("default", false) => AnsiColors::Default,
+        ("black", true) => AnsiColors::BrightBlack,
+        ("red", true) => AnsiColors::BrightRed,
+        ("green", true) => AnsiColors::BrightGreen,
+        ("yellow", true) => AnsiColors::BrightYellow,
+        ("blue", true) => AnsiColors::BrightBlue,
+        ("magenta" | "purple", true) => AnsiColors::BrightMagenta,
+        ("cyan", true) => AnsiColors::BrightCyan,
+        ("white", true) => AnsiColors::BrightWhite,
+        _ => return None,
+    };
+
+    Some(Color::Ansi(color))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(text: &str) -> Result<Vec<u8>, String> {
+        ThemeStyle::try_from(text.to_owned())
+            .map(|parsed| sgr_codes(parsed.style))
+    }
+
+    #[test]
+    fn parses_colour_names() {
+        assert_eq!(codes("red"), Ok(vec![31]));
+        assert_eq!(codes("Purple"), Ok(vec![35]));
+        assert_eq!(codes("magenta"), codes("purple"));
+        assert_eq!(codes("bright_blue"), Ok(vec![94]));
+        assert_eq!(codes("bright_purple"), Ok(vec![95]));
+    }
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(codes("#ff8800"), Ok(vec![38, 2, 255, 136, 0]));
+        assert_eq!(codes("on #000000"), Ok(vec![48, 2, 0, 0, 0]));
+    }
+
+    #[test]
+    fn parses_backgrounds_and_effects() {
+        assert_eq!(codes("bold white on blue"), Ok(vec![37, 44, 1]));
+        assert_eq!(codes("dim italic underline"), Ok(vec![2, 3, 4]));
+        assert_eq!(codes("strikethrough"), Ok(vec![9]));
+        assert_eq!(codes(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn rejects_invalid_styles() {
+        for text in ["#ff88", "#gg8800", "#ff88000", "bright_default"] {
+            assert!(codes(text).is_err(), "{text}");
+        }
+
+        assert_eq!(
+            codes("bold sparkly"),
+            Err("invalid style `bold sparkly`: unknown colour or effect \
+                 `sparkly`"
+                .to_owned())
+        );
+        assert_eq!(
+            codes("red on"),
+            Err("invalid style `red on`: expected a colour after `on`"
+                .to_owned())
+        );
+    }
+
+    #[test]
+    fn keeps_the_written_text() {
+        let style =
+            ThemeStyle::try_from("Bold  Red".to_owned()).expect("valid style");
+
+        assert_eq!(String::from(style), "Bold  Red");
+    }
+}
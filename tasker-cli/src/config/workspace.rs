@@ -0,0 +1,127 @@
+use super::{Configuration, Language, Theme};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// Name of the workspace made of the top-level settings, used when no other
+/// is selected.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Settings a workspace can override on top of the top-level ones.
+pub const WORKSPACE_SETTINGS: [&str; 3] = ["to_do_path", "language", "theme"];
+
+/// A named list of Tasks, kept in its own file with its own IDs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub to_do_path: Utf8PathBuf,
+    /// Language used while the workspace is selected, instead of the
+    /// top-level one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    /// Colours used while the workspace is selected, on top of the top-level
+    /// ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+}
+
+impl Configuration {
+    /// Returns the name of the workspace in use.
+    #[must_use]
+    pub fn active_workspace(&self) -> &str {
+        self.workspace.as_deref().unwrap_or(DEFAULT_WORKSPACE)
+    }
+
+    /// Returns the name and Tasks file of every workspace, starting with the
+    /// default one.
+    #[must_use]
+    pub fn workspace_paths(&self) -> Vec<(&str, &Utf8Path)> {
+        let default = if self.active_workspace() == DEFAULT_WORKSPACE {
+            &self.to_do_path
+        } else {
+            &self.default_workspace_path
+        };
+
+        std::iter::once((DEFAULT_WORKSPACE, default.as_path()))
+            .chain(self.workspaces.iter().map(|(name, workspace)| {
+                (name.as_str(), workspace.to_do_path.as_path())
+            }))
+            .collect()
+    }
+
+    /// Returns the Tasks file of the workspace with the given name, if it
+    /// exists.
+    #[must_use]
+    pub fn workspace_path(&self, name: &str) -> Option<&Utf8Path> {
+        self.workspace_paths()
+            .into_iter()
+            .find(|(workspace, _)| *workspace == name)
+            .map(|(_, path)| path)
+    }
+
+    /// Returns whether a workspace with the given name exists.
+    #[must_use]
+    pub fn has_workspace(&self, name: &str) -> bool {
+        name == DEFAULT_WORKSPACE || self.workspaces.contains_key(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(active: Option<&str>) -> Configuration {
+        let mut config = Configuration::fallback(Language::English);
+        config.to_do_path = Utf8PathBuf::from("/home/me/todo.ron");
+        config.default_workspace_path = config.to_do_path.clone();
+
+        for name in ["work", "home"] {
+            config.workspaces.insert(
+                name.to_string(),
+                Workspace {
+                    to_do_path: Utf8PathBuf::from(format!("/{name}.ron")),
+                    language: None,
+                    theme: None,
+                },
+            );
+        }
+
+        if let Some(name) = active {
+            config.workspace = Some(name.to_string());
+            config.to_do_path = Utf8PathBuf::from(format!("/{name}.ron"));
+        }
+
+        config
+    }
+
+    #[test]
+    fn resolves_workspaces_by_name() {
+        let config = config(None);
+
+        assert_eq!(config.active_workspace(), DEFAULT_WORKSPACE);
+        assert_eq!(
+            config.workspace_path(DEFAULT_WORKSPACE),
+            Some(Utf8Path::new("/home/me/todo.ron"))
+        );
+        assert_eq!(
+            config.workspace_path("work"),
+            Some(Utf8Path::new("/work.ron"))
+        );
+        assert_eq!(config.workspace_path("Work"), None);
+        assert!(config.has_workspace("home"));
+        assert!(!config.has_workspace("school"));
+    }
+
+    #[test]
+    fn keeps_the_default_workspace_while_another_is_in_use() {
+        let config = config(Some("work"));
+
+        assert_eq!(config.active_workspace(), "work");
+        assert_eq!(
+            config.workspace_paths(),
+            [
+                (DEFAULT_WORKSPACE, Utf8Path::new("/home/me/todo.ron")),
+                ("work", Utf8Path::new("/work.ron")),
+                ("home", Utf8Path::new("/home.ron")),
+            ]
+        );
+    }
+}
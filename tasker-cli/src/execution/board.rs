@@ -0,0 +1,270 @@
+use super::helpers::{filter_tasks, terminal_width, truncate, wrap};
+use crate::{cli::ListTasks, config::Configuration};
+use anstream::print;
+use itertools::Itertools;
+use lib_tasker::todos::{State, Task, ToDo};
+use owo_colors::OwoColorize;
+use unicode_width::UnicodeWidthStr;
+
+const SEPARATOR: &str = " │ ";
+const MIN_COLUMN_WIDTH: usize = 12;
+const MAX_DESCRIPTION_LINES: usize = 3;
+
+/// A line of a column, alongside its width without colours.
+struct Cell {
+    text: String,
+    width: usize,
+}
+
+impl Cell {
+    const fn new(text: String, width: usize) -> Self {
+        Self { text, width }
+    }
+
+    const fn blank() -> Self {
+        Self::new(String::new(), 0)
+    }
+}
+
+/// Prints the Tasks matching the given filters in side-by-side columns, one
+/// per state.
+pub fn print_board(to_do: &ToDo, config: &Configuration, options: &ListTasks) {
+    let tasks = filter_tasks(to_do, options);
+
+    print!("{}", board_within(&tasks, config, terminal_width()));
+}
+
+/// Lays out the given Tasks in a column per state, sharing the given width
+/// between them unless it leaves them too narrow.
+fn board_within(
+    tasks: &[Task],
+    config: &Configuration,
+    width: usize,
+) -> String {
+    let states = [State::ToDo, State::Doing, State::Waiting, State::Done];
+
+    let width = (width.saturating_sub(SEPARATOR.width() * (states.len() - 1))
+        / states.len())
+    .max(MIN_COLUMN_WIDTH);
+
+    let columns = states
+        .iter()
+        .map(|&state| {
+            let tasks = tasks.iter().filter(|task| task.state == state);
+            column(state, tasks.collect(), config, width)
+        })
+        .collect_vec();
+
+    let height = columns.iter().map(Vec::len).max().unwrap_or(0);
+    let mut output = String::new();
+
+    for row in 0..height {
+        let line = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let blank = Cell::blank();
+                let cell = column.get(row).unwrap_or(&blank);
+
+                if index + 1 == columns.len() {
+                    cell.text.clone()
+                } else {
+                    let padding = " ".repeat(width.saturating_sub(cell.width));
+                    format!("{}{padding}", cell.text)
+                }
+            })
+            .join(&format!(
+                " {} ",
+                SEPARATOR.trim().style(config.theme.muted())
+            ));
+
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    output
+}
+
+fn column(
+    state: State,
+    tasks: Vec<&Task>,
+    config: &Configuration,
+    width: usize,
+) -> Vec<Cell> {
+    let (title, limit) = match state {
+        State::ToDo => ("state_to_do", config.wip_limits.to_do),
+        State::Doing => ("state_doing", config.wip_limits.doing),
+        State::Waiting => ("state_waiting", config.wip_limits.waiting),
+        State::Done => ("state_done", config.wip_limits.done),
+    };
+
+    let title = config.text(title);
+
+    let count = limit.map_or_else(
+        || tasks.len().to_string(),
+        |limit| format!("{}/{limit}", tasks.len()),
+    );
+
+    let header = truncate(&format!("{title} ({count})"), width);
+    let overloaded = limit.is_some_and(|limit| tasks.len() > limit);
+
+    let styled_header = if overloaded {
+        header.style(config.theme.error()).bold().to_string()
+    } else {
+        header.style(config.theme.state(state)).bold().to_string()
+    };
+
+    let mut cells = vec![
+        Cell::new(styled_header, header.width()),
+        Cell::new(
+            "─".repeat(width).style(config.theme.muted()).to_string(),
+            width,
+        ),
+    ];
+
+    for task in tasks {
+        cells.extend(card(task, width, config));
+        cells.push(Cell::blank());
+    }
+
+    cells
+}
+
+fn card(task: &Task, width: usize, config: &Configuration) -> Vec<Cell> {
+    let id = format!("{}. ", task.id);
+    let indent = id.width();
+
+    let mut lines = wrap(&task.description, width.saturating_sub(indent));
+
+    if lines.len() > MAX_DESCRIPTION_LINES {
+        let rest = lines.split_off(MAX_DESCRIPTION_LINES - 1).join(" ");
+        lines.push(truncate(&rest, width.saturating_sub(indent)));
+    }
+
+    let mut cells = lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let line_width = indent + line.width();
+
+            if index == 0 {
+                Cell::new(
+                    format!("{}{line}", id.style(config.theme.id())),
+                    line_width,
+                )
+            } else {
+                Cell::new(format!("{}{line}", " ".repeat(indent)), line_width)
+            }
+        })
+        .collect_vec();
+
+    if !task.tags.is_empty() {
+        let tags = truncate(
+            &task.tags.iter().map(|tag| format!("#{tag}")).join(" "),
+            width.saturating_sub(indent),
+        );
+
+        cells.push(Cell::new(
+            format!("{}{}", " ".repeat(indent), tags.style(config.theme.tag())),
+            indent + tags.width(),
+        ));
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use anstream::adapter::strip_str;
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task::create("Write the documentation of every command")
+                .id(1)
+                .tag("docs")
+                .build(),
+            Task::create("Fix it").id(2).build(),
+            Task::create("Ship it").id(3).state(State::Doing).build(),
+        ]
+    }
+
+    fn lines(board: &str) -> Vec<String> {
+        strip_str(board)
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn lays_out_a_column_per_state() {
+        let config = Configuration::fallback(Language::English);
+        let board = lines(&board_within(&tasks(), &config, 80));
+
+        assert_eq!(
+            board[..4],
+            [
+                "To-Do (2)         │ Doing (1)         │ Waiting (0)       \
+                 │ Done (0)",
+                "───────────────── │ ───────────────── │ ───────────────── \
+                 │ ─────────────────",
+                "1. Write the      │ 3. Ship it        │                   │",
+                "   documentation  │                   │                   │",
+            ]
+        );
+        assert!(board[4].starts_with("   of every comm… │"));
+        assert!(board[5].starts_with("   #docs          │"));
+        assert!(board[7].starts_with("2. Fix it         │"));
+        assert!(board.iter().all(|line| line == line.trim_end()));
+    }
+
+    #[test]
+    fn keeps_columns_readable_when_narrow() {
+        let config = Configuration::fallback(Language::English);
+        let board = lines(&board_within(&tasks(), &config, 20));
+
+        let rule = "─".repeat(MIN_COLUMN_WIDTH);
+
+        assert_eq!(board[1], vec![rule; 4].join(SEPARATOR));
+    }
+
+    #[test]
+    fn marks_columns_over_their_wip_limit() {
+        let mut config = Configuration::fallback(Language::English);
+        config.wip_limits.to_do = Some(1);
+        config.wip_limits.doing = Some(1);
+
+        let to_do = column(
+            State::ToDo,
+            tasks()
+                .iter()
+                .filter(|task| task.state == State::ToDo)
+                .collect(),
+            &config,
+            20,
+        );
+        let doing = column(
+            State::Doing,
+            tasks()
+                .iter()
+                .filter(|task| task.state == State::Doing)
+                .collect(),
+            &config,
+            20,
+        );
+
+        assert_eq!(
+            to_do[0].text,
+            "To-Do (2/1)".style(config.theme.error()).bold().to_string()
+        );
+        assert_eq!(
+            doing[0].text,
+            "Doing (1/1)"
+                .style(config.theme.state(State::Doing))
+                .bold()
+                .to_string()
+        );
+    }
+}
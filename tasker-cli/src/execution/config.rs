@@ -0,0 +1,415 @@
+use super::{
+    context::unknown_context, editor::open_editor, helpers::plain_width,
+    workspace::unknown_workspace,
+};
+use crate::{
+    cli::ConfigCommand,
+    config::{
+        check_file, ConfigDocument, Configuration, Diagnostic, Origin, Problem,
+        Repair, SettingError,
+    },
+    i18n::{catalogue, Catalogue},
+};
+use anstream::{eprintln, println};
+use anyhow::{anyhow, bail};
+use camino::Utf8PathBuf;
+use owo_colors::OwoColorize;
+
+/// Reads or changes the settings of the configuration file at the given path.
+///
+/// # Errors
+///
+/// Returns an error if the setting doesn't exist, if the new value isn't
+/// valid or if the file couldn't be read or written.
+pub fn manage_config(
+    command: &ConfigCommand,
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    match command {
+        ConfigCommand::Get(get) => match config.setting(&get.key).as_slice() {
+            [] => bail!(
+                "{}",
+                config.format("setting_not_set", &[("key", &get.key)])
+            ),
+            [(name, value)] if *name == get.key => {
+                // Plain text is easier to use in scripts than a quoted string.
+                match value {
+                    toml::Value::String(text) => println!("{text}"),
+                    value => println!("{value}"),
+                }
+            }
+            settings => print_settings(settings, config),
+        },
+        ConfigCommand::Set(set) => {
+            let mut document = open_document(path, config)?;
+            let updated = document
+                .set(&set.key, &set.value)
+                .map_err(|err| describe(err, path, config))?;
+
+            save_document(&document, path, config)?;
+
+            println!(
+                "{}",
+                config.text("setting_changed").style(config.theme.success())
+            );
+            print_settings(&updated.setting(&set.key), config);
+        }
+        ConfigCommand::Unset(unset) => {
+            let mut document = open_document(path, config)?;
+            let updated = document
+                .unset(&unset.key)
+                .map_err(|err| describe(err, path, config))?;
+
+            save_document(&document, path, config)?;
+
+            println!(
+                "{}",
+                config.text("setting_removed").style(config.theme.success())
+            );
+            print_settings(&updated.setting(&unset.key), config);
+        }
+        ConfigCommand::List(list) => {
+            if list.show_origin {
+                print_settings_with_origin(config);
+            } else {
+                print_settings(&config.settings(), config);
+            }
+        }
+        ConfigCommand::Edit => {
+            open_editor(path.as_std_path(), config)?;
+
+            check_edited(path, config)?;
+        }
+        ConfigCommand::Doctor(doctor) => {
+            repair_config(path, doctor.dry_run, config)?;
+        }
+        ConfigCommand::Path => println!("{path}"),
+    }
+
+    Ok(())
+}
+
+/// Checks that the configuration file still loads after being edited.
+fn check_edited(
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let invalid = |reason: &dyn std::fmt::Display| {
+        anyhow!("{}", config.format("config_invalid", &[("reason", reason)]))
+    };
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| invalid(&err.style(config.theme.error())))?;
+
+    check_file(path, &contents).map_err(|diagnostic| {
+        invalid(&describe_diagnostic(
+            &diagnostic,
+            catalogue(config.language()),
+        ))
+    })?;
+
+    Ok(())
+}
+
+/// Brings the configuration file up to date, keeping a backup of it.
+fn repair_config(
+    path: &Utf8PathBuf,
+    dry_run: bool,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let mut document = open_document(path, config)?;
+    let repairs = document.repair().map_err(|diagnostic| {
+        anyhow!(
+            "{}",
+            describe_diagnostic(&diagnostic, catalogue(config.language()))
+        )
+    })?;
+
+    if repairs.is_empty() {
+        println!(
+            "{}",
+            config
+                .text("doctor_up_to_date")
+                .style(config.theme.success())
+        );
+        return Ok(());
+    }
+
+    for repair in &repairs {
+        let message = match repair {
+            Repair::Renamed(from, to) => {
+                config.format("doctor_renamed", &[("from", from), ("to", to)])
+            }
+            Repair::Removed(key) => {
+                config.format("doctor_removed", &[("key", key)])
+            }
+            Repair::Reset(key, reason) => config.format(
+                "doctor_reset",
+                &[
+                    ("key", key),
+                    ("reason", &reason.style(config.theme.error())),
+                ],
+            ),
+            Repair::Added(key) => {
+                config.format("doctor_added", &[("key", key)])
+            }
+        };
+
+        println!("- {message}");
+    }
+
+    if dry_run {
+        println!(
+            "{}",
+            config.text("doctor_dry_run").style(config.theme.info())
+        );
+        return Ok(());
+    }
+
+    let backup = Utf8PathBuf::from(format!("{path}.bak"));
+
+    std::fs::copy(path, &backup)
+        .and_then(|_| document.save())
+        .map_err(|err| {
+            anyhow!(
+                "{}",
+                config.format(
+                    "config_save_failed",
+                    &[
+                        ("path", &path),
+                        ("reason", &err.style(config.theme.error()))
+                    ]
+                )
+            )
+        })?;
+
+    println!(
+        "{}",
+        config
+            .format("doctor_saved", &[("path", &backup)])
+            .style(config.theme.success())
+    );
+
+    Ok(())
+}
+
+/// Prints the problems found while loading the configuration that didn't
+/// stop it, such as unknown settings.
+pub fn print_warnings(config: &Configuration) {
+    for warning in &config.warnings {
+        eprintln!(
+            "{}: {}",
+            config
+                .text("diagnostic_warning")
+                .style(config.theme.warning()),
+            describe_diagnostic(warning, catalogue(config.language()))
+        );
+    }
+}
+
+/// Describes a problem of a configuration file, followed by a suggested fix
+/// on a line of its own.
+pub fn describe_diagnostic(
+    diagnostic: &Diagnostic,
+    catalogue: &'static Catalogue,
+) -> String {
+    let key = diagnostic.key.as_deref().unwrap_or_default();
+    let describe = |reason: &str| {
+        catalogue.format(
+            match diagnostic.problem {
+                Problem::Syntax(_) => "diagnostic_syntax",
+                Problem::InvalidValue(_) => "diagnostic_invalid",
+                Problem::UnknownKey => "diagnostic_unknown",
+                Problem::Untrusted => "diagnostic_untrusted",
+            },
+            &[
+                ("path", &diagnostic.path),
+                ("line", &diagnostic.line),
+                ("column", &diagnostic.column),
+                ("key", &key),
+                ("reason", &reason),
+            ],
+        )
+    };
+
+    let message = match &diagnostic.problem {
+        Problem::Syntax(reason) | Problem::InvalidValue(reason) => {
+            describe(reason)
+        }
+        Problem::UnknownKey | Problem::Untrusted => describe(""),
+    };
+
+    let help = match (&diagnostic.suggestion, &diagnostic.problem) {
+        (Some(name), _) => {
+            catalogue.format("suggest_did_you_mean", &[("name", name)])
+        }
+        (None, Problem::InvalidValue(_)) if diagnostic.key.is_some() => {
+            catalogue.format("suggest_unset", &[("key", &key)])
+        }
+        (None, Problem::UnknownKey) => {
+            catalogue.text("suggest_doctor").to_string()
+        }
+        (None, Problem::Untrusted) => catalogue.format(
+            "suggest_trust",
+            &[(
+                "directory",
+                &diagnostic.path.parent().unwrap_or(&diagnostic.path),
+            )],
+        ),
+        (None, _) => catalogue.text("suggest_edit").to_string(),
+    };
+
+    format!("{message}\n  {}: {help}", catalogue.text("diagnostic_help"))
+}
+
+/// Describes an environment variable with an invalid value, with how to fix
+/// it.
+pub fn describe_variable(
+    variable: &str,
+    value: &str,
+    reason: &str,
+    catalogue: &'static Catalogue,
+) -> String {
+    format!(
+        "{}\n  {}: {}",
+        catalogue.format(
+            "config_env_invalid",
+            &[
+                ("variable", &variable),
+                ("value", &value),
+                ("reason", &reason)
+            ],
+        ),
+        catalogue.text("diagnostic_help"),
+        catalogue.format("suggest_env", &[("variable", &variable)])
+    )
+}
+
+fn print_settings(settings: &[(String, toml::Value)], config: &Configuration) {
+    for (name, value) in settings {
+        println!("{} = {value}", name.style(config.theme.label()));
+    }
+}
+
+/// Prints every setting after where its value came from, aligned like
+/// `git config --show-origin`.
+fn print_settings_with_origin(config: &Configuration) {
+    let settings: Vec<_> = config
+        .settings()
+        .into_iter()
+        .map(|(name, value)| {
+            (origin_label(&config.origin(&name), config), name, value)
+        })
+        .collect();
+
+    let width = settings
+        .iter()
+        .map(|(origin, _, _)| plain_width(origin))
+        .max()
+        .unwrap_or_default();
+
+    for (origin, name, value) in settings {
+        let padding = " ".repeat(width - plain_width(&origin));
+
+        println!(
+            "{}{padding}  {} = {value}",
+            origin.style(config.theme.info()),
+            name.style(config.theme.label())
+        );
+    }
+}
+
+/// Describes where a setting came from, with the file or variable if any.
+pub fn origin_label(origin: &Origin, config: &Configuration) -> String {
+    match origin {
+        Origin::Default => config.text("origin_default").to_string(),
+        Origin::System(path) => {
+            format!("{}: {path}", config.text("origin_system"))
+        }
+        Origin::User(path) => format!("{}: {path}", config.text("origin_user")),
+        Origin::Local(path) => {
+            format!("{}: {path}", config.text("origin_local"))
+        }
+        Origin::Environment(variable) => {
+            format!("{}: {variable}", config.text("origin_environment"))
+        }
+        Origin::Discovered => config.text("origin_discovered").to_string(),
+        Origin::Workspace(name) => {
+            format!("{}: {name}", config.text("origin_workspace"))
+        }
+        Origin::Flag(flag) => format!("{}: {flag}", config.text("origin_flag")),
+    }
+}
+
+pub fn open_document(
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<ConfigDocument> {
+    ConfigDocument::open(path).map_err(|err| describe(err, path, config))
+}
+
+pub fn save_document(
+    document: &ConfigDocument,
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    document.save().map_err(|err| {
+        anyhow!(
+            "{}",
+            config.format(
+                "config_save_failed",
+                &[
+                    ("path", &path),
+                    ("reason", &err.style(config.theme.error()))
+                ]
+            )
+        )
+    })
+}
+
+/// Turns a setting error into a message in the configured language.
+pub fn describe(
+    err: SettingError,
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Error {
+    let message = match err {
+        SettingError::Io(err) => config.format(
+            "config_unreadable",
+            &[
+                ("path", &path),
+                ("reason", &err.style(config.theme.error())),
+            ],
+        ),
+        SettingError::Malformed(diagnostic) => {
+            describe_diagnostic(&diagnostic, catalogue(config.language()))
+        }
+        SettingError::Unknown(key) => {
+            config.format("setting_unknown", &[("key", &key)])
+        }
+        SettingError::NotSet(key) => {
+            config.format("setting_not_set", &[("key", &key)])
+        }
+        SettingError::Invalid(key, reason) => config.format(
+            "setting_invalid",
+            &[
+                ("key", &key),
+                ("reason", &reason.style(config.theme.error())),
+            ],
+        ),
+        SettingError::Unwritable(path, err) => config.format(
+            "setting_unwritable",
+            &[
+                ("path", &path),
+                ("reason", &err.style(config.theme.error())),
+            ],
+        ),
+        SettingError::UnknownWorkspace(name) => {
+            unknown_workspace(&name, config)
+        }
+        SettingError::UnknownContext(name) => unknown_context(&name, config),
+    };
+
+    anyhow!("{message}")
+}
@@ -0,0 +1,211 @@
+use super::config::{describe, open_document, save_document};
+use crate::{
+    cli::ContextCommand,
+    config::{Configuration, SettingError},
+};
+use anstream::println;
+use anyhow::bail;
+use camino::Utf8PathBuf;
+use itertools::Itertools;
+use lib_tasker::todos::Filter;
+use owo_colors::OwoColorize;
+
+/// Selects, clears or shows the active context, stored in the configuration
+/// file at the given path.
+///
+/// # Errors
+///
+/// Returns an error if the context doesn't exist or has an invalid filter, or
+/// if the configuration file couldn't be read or written.
+pub fn manage_context(
+    command: &ContextCommand,
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    match command {
+        ContextCommand::Set(set) => {
+            let (expression, _) = find_context(&set.name, config)?;
+
+            let mut document = open_document(path, config)?;
+            document
+                .set("context", &set.name)
+                .map_err(|err| describe(err, path, config))?;
+
+            save_document(&document, path, config)?;
+
+            println!(
+                "{}: {expression}",
+                config
+                    .format("context_set", &[("name", &set.name)])
+                    .style(config.theme.success())
+            );
+        }
+        ContextCommand::Clear => {
+            let mut document = open_document(path, config)?;
+
+            match document.unset("context") {
+                Ok(_) | Err(SettingError::NotSet(_)) => {}
+                Err(err) => return Err(describe(err, path, config)),
+            }
+
+            save_document(&document, path, config)?;
+
+            println!(
+                "{}",
+                config.text("context_cleared").style(config.theme.success())
+            );
+        }
+        ContextCommand::Show => match &config.context {
+            Some(name) => {
+                let (expression, _) = find_context(name, config)?;
+
+                println!("{}: {expression}", name.style(config.theme.label()));
+            }
+            None => println!("{}", config.text("no_context")),
+        },
+    }
+
+    Ok(())
+}
+
+/// Returns the filter of the active context, or one matching every Task if
+/// there's none.
+///
+/// # Errors
+///
+/// Returns an error if the active context doesn't exist or has an invalid
+/// filter.
+pub fn context_filter(config: &Configuration) -> anyhow::Result<Filter> {
+    config.context.as_ref().map_or(Ok(Filter::Any), |name| {
+        find_context(name, config).map(|(_, filter)| filter)
+    })
+}
+
+/// Returns the project and tags new Tasks get: the given ones, or else the
+/// ones the context implies. Tags given on their own replace those of the
+/// context rather than adding to them.
+pub fn context_defaults(
+    context: &Filter,
+    project: Option<String>,
+    tags: Option<Vec<String>>,
+) -> (Option<String>, Vec<String>) {
+    let project =
+        project.or_else(|| context.implied_project().map(ToString::to_string));
+    let tags = tags.unwrap_or_else(|| {
+        context
+            .implied_tags()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect()
+    });
+
+    (project, tags)
+}
+
+/// Describes the context with the given name not existing, listing the
+/// ones that do.
+pub fn unknown_context(name: &str, config: &Configuration) -> String {
+    let available = config
+        .contexts
+        .keys()
+        .map(|name| format!("`{name}`"))
+        .join(", ");
+
+    config.format(
+        "context_unknown",
+        &[("name", &name), ("available", &available)],
+    )
+}
+
+/// Returns the filter of the context with the given name, as written and
+/// parsed.
+fn find_context<'a>(
+    name: &str,
+    config: &'a Configuration,
+) -> anyhow::Result<(&'a str, Filter)> {
+    let Some(expression) = config.contexts.get(name) else {
+        bail!("{}", unknown_context(name, config));
+    };
+
+    match expression.parse() {
+        Ok(filter) => Ok((expression, filter)),
+        Err(err) => bail!(
+            "{}: {err}",
+            config.format("context_failed", &[("name", &name)])
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+
+    fn config(active: Option<&str>) -> Configuration {
+        let mut config = Configuration::fallback(Language::English);
+        config
+            .contexts
+            .insert("work".to_string(), "project:Work and +office".to_string());
+        config
+            .contexts
+            .insert("broken".to_string(), "state:bored".to_string());
+        config.context = active.map(ToString::to_string);
+
+        config
+    }
+
+    #[test]
+    fn resolves_the_active_context() {
+        assert_eq!(
+            context_filter(&config(None)).expect("no context is active"),
+            Filter::Any
+        );
+        assert_eq!(
+            context_filter(&config(Some("work"))).expect("the context exists"),
+            "project:Work and +office"
+                .parse::<Filter>()
+                .expect("the filter is valid")
+        );
+    }
+
+    #[test]
+    fn reports_unknown_and_invalid_contexts() {
+        let err = context_filter(&config(Some("home")))
+            .expect_err("the context doesn't exist");
+
+        assert_eq!(
+            err.to_string(),
+            "There's no context named `home`. Available: `work`, `broken`"
+        );
+
+        let err = context_filter(&config(Some("broken")))
+            .expect_err("the filter is invalid");
+
+        assert!(err.to_string().starts_with(
+            &config(None).format("context_failed", &[("name", &"broken")])
+        ));
+    }
+
+    #[test]
+    fn new_tasks_get_the_context_only_for_what_isnt_given() {
+        let context =
+            context_filter(&config(Some("work"))).expect("the context exists");
+
+        assert_eq!(
+            context_defaults(&context, None, None),
+            (Some("Work".to_string()), vec!["office".to_string()])
+        );
+        assert_eq!(
+            context_defaults(
+                &context,
+                Some("Home".to_string()),
+                Some(vec!["chores".to_string()])
+            ),
+            (Some("Home".to_string()), vec!["chores".to_string()])
+        );
+        assert_eq!(
+            context_defaults(&Filter::Any, None, Some(Vec::new())),
+            (None, Vec::new())
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use crate::config::Configuration;
+use anyhow::{bail, Context};
+use chrono::{Local, NaiveDate, Utc};
+use lib_tasker::todos::{Estimate, Recurrence, State, ToDo, Transition};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const ERROR_PREFIX: &str = "# ERROR: ";
+
+/// A Task as presented to the user within their editor.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EditableTask {
+    id: usize,
+    description: String,
+    state: State,
+    project: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<usize>,
+    #[serde(default)]
+    depends_on: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurrence: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EditableTasks {
+    #[serde(default)]
+    tasks: Vec<EditableTask>,
+}
+
+/// Opens the Tasks with the given ids in the user's editor and applies the
+/// changes made to them, re-opening the editor with the error annotated on
+/// top for as long as they are invalid. Returns what followed from the
+/// changes, whose found ids are the Tasks that actually changed.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file couldn't be written or read, if the
+/// editor failed to run or if the user cancelled by emptying the file.
+pub fn edit_in_editor(
+    to_do: &mut ToDo,
+    ids: &[usize],
+    config: &Configuration,
+) -> anyhow::Result<Transition> {
+    let editable = EditableTasks {
+        tasks: ids
+            .iter()
+            .filter_map(|&id| to_do.get_task(id))
+            .map(|task| EditableTask {
+                id: task.id,
+                description: task.description.clone(),
+                state: task.state,
+                project: task.project.clone(),
+                tags: task.tags.iter().cloned().collect(),
+                parent: task.parent,
+                depends_on: task.depends_on.iter().copied().collect(),
+                due: task.due,
+                recurrence: task.recurrence.as_ref().map(ToString::to_string),
+                estimate: task.estimate.map(|estimate| estimate.to_string()),
+            })
+            .collect(),
+    };
+
+    let file = tempfile::Builder::new()
+        .prefix("tasker-edit-")
+        .suffix(".toml")
+        .tempfile()?;
+    let path = file.path();
+    let mut contents = format!(
+        "{}\n{}",
+        config.text("editor_header"),
+        toml::to_string_pretty(&editable)?
+    );
+
+    let result = loop {
+        std::fs::write(path, &contents)?;
+        open_editor(path, config)?;
+
+        let mut edited = String::new();
+
+        for line in std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.starts_with(ERROR_PREFIX))
+        {
+            edited.push_str(line);
+            edited.push('\n');
+        }
+
+        if edited
+            .lines()
+            .all(|line| line.trim().is_empty() || line.trim().starts_with('#'))
+        {
+            break Err(anyhow::anyhow!("{}", config.text("edit_cancelled")));
+        }
+
+        match apply_changes(to_do, ids, &edited, config) {
+            Ok((edited_to_do, follow_ups)) => {
+                *to_do = edited_to_do;
+                break Ok(follow_ups);
+            }
+            Err(err) => {
+                contents = String::new();
+
+                for line in format!("{err:#}").lines() {
+                    contents.push_str(ERROR_PREFIX);
+                    contents.push_str(line);
+                    contents.push('\n');
+                }
+
+                contents.push_str(&edited);
+            }
+        }
+    };
+
+    drop(file);
+
+    result
+}
+
+/// Validates the edited Tasks and applies them to a copy of the given `ToDo`,
+/// moving the ones whose state changed through their transition. Returns the
+/// copy alongside the transition, whose found ids are the Tasks that changed.
+fn apply_changes(
+    to_do: &ToDo,
+    ids: &[usize],
+    contents: &str,
+    config: &Configuration,
+) -> anyhow::Result<(ToDo, Transition)> {
+    let editable: EditableTasks = toml::from_str(contents)?;
+    let mut edited = to_do.clone();
+    let mut seen = Vec::new();
+    let mut changed = Vec::new();
+    let mut follow_ups = Transition::default();
+    let now = Local::now();
+
+    for task in editable.tasks {
+        let id = task.id;
+
+        if !ids.contains(&id) {
+            bail!("{}", config.format("task_not_selected", &[("id", &id)]));
+        }
+
+        if seen.contains(&id) {
+            bail!("{}", config.format("task_repeated", &[("id", &id)]));
+        }
+
+        seen.push(id);
+
+        if task.description.trim().is_empty() {
+            bail!(
+                "{}",
+                config.format("task_without_description", &[("id", &id)])
+            );
+        }
+
+        let recurrence = task
+            .recurrence
+            .as_deref()
+            .map(str::parse::<Recurrence>)
+            .transpose()?;
+
+        let estimate = task
+            .estimate
+            .as_deref()
+            .map(str::parse::<Estimate>)
+            .transpose()?;
+
+        let original = edited
+            .get_task(id)
+            .cloned()
+            .with_context(|| config.format("task_missing", &[("id", &id)]))?;
+
+        if original.parent != task.parent {
+            edited.set_parent(id, task.parent)?;
+        }
+
+        if original.depends_on.iter().ne(task.depends_on.iter()) {
+            edited.set_dependencies(id, task.depends_on)?;
+        }
+
+        if let Some(current) =
+            edited.tasks.iter_mut().find(|task| task.id == id)
+        {
+            current.description = task.description;
+            current.project = task.project;
+            current.replace_tags(task.tags);
+            current.due = task.due;
+            current.recurrence = recurrence;
+            current.estimate = estimate;
+        }
+
+        if task.state != original.state {
+            let transition = edited.transition(
+                &[id],
+                task.state,
+                now.date_naive(),
+                now.with_timezone(&Utc),
+            )?;
+
+            follow_ups.unblocked.extend(transition.unblocked);
+            follow_ups.recurrences.extend(transition.recurrences);
+        }
+
+        if edited.get_task(id) != Some(&original) {
+            changed.push(id);
+        }
+    }
+
+    follow_ups.unblocked.sort_unstable();
+    follow_ups.unblocked.dedup();
+    follow_ups.matches.found.clone_from(&changed);
+
+    Ok((edited, follow_ups))
+}
+
+/// Opens the given file in the editor set in `$VISUAL` or `$EDITOR`, and
+/// waits for it to close.
+pub fn open_editor(path: &Path, config: &Configuration) -> anyhow::Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) { "notepad" } else { "vi" }.to_string()
+        });
+
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| {
+            config.format("editor_failed", &[("editor", &editor)])
+        })?;
+
+    if !status.success() {
+        bail!(
+            "{}",
+            config.format(
+                "editor_exited",
+                &[("editor", &editor), ("status", &status)]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use lib_tasker::todos::Task;
+
+    fn config() -> Configuration {
+        Configuration::fallback(Language::English)
+    }
+
+    fn sample_to_do() -> ToDo {
+        ToDo::from(vec![
+            Task::create("Write the docs").id(1).project("Docs").build(),
+            Task::create("Publish the docs")
+                .id(2)
+                .project("Docs")
+                .depends_on([1])
+                .build(),
+            Task::create("Water the plants")
+                .id(3)
+                .recurrence("daily".parse().expect("should be valid"))
+                .build(),
+        ])
+    }
+
+    fn apply(
+        ids: &[usize],
+        contents: &str,
+    ) -> anyhow::Result<(ToDo, Transition)> {
+        apply_changes(&sample_to_do(), ids, contents, &config())
+    }
+
+    fn error(ids: &[usize], contents: &str) -> String {
+        apply(ids, contents)
+            .expect_err("the changes should be rejected")
+            .to_string()
+    }
+
+    #[test]
+    fn applies_edited_fields() {
+        let (to_do, follow_ups) = apply(
+            &[1, 2],
+            r#"
+            [[tasks]]
+            id = 1
+            description = "Write the manual"
+            state = "ToDo"
+            project = "Manual"
+            tags = ["writing"]
+            estimate = "2h"
+
+            [[tasks]]
+            id = 2
+            description = "Publish the docs"
+            state = "ToDo"
+            project = "Docs"
+            depends_on = [1]
+            "#,
+        )
+        .expect("the changes should be valid");
+
+        let task = to_do.get_task(1).expect("the Task should exist");
+
+        assert_eq!(task.description, "Write the manual");
+        assert_eq!(task.project, "Manual");
+        assert!(task.tags.contains("writing"));
+        assert_eq!(task.estimate, "2h".parse().ok());
+        assert_eq!(to_do.get_task(2), sample_to_do().get_task(2));
+        assert_eq!(follow_ups.matches.found, [1]);
+    }
+
+    #[test]
+    fn changes_states_through_the_transition() {
+        let (to_do, follow_ups) = apply(
+            &[1, 3],
+            r#"
+            [[tasks]]
+            id = 1
+            description = "Write the docs"
+            state = "Done"
+            project = "Docs"
+
+            [[tasks]]
+            id = 3
+            description = "Water the plants"
+            state = "Done"
+            project = "Inbox"
+            recurrence = "daily"
+            "#,
+        )
+        .expect("the changes should be valid");
+
+        let task = to_do.get_task(1).expect("the Task should exist");
+
+        assert_eq!(task.state, State::Done);
+        assert!(task.completed_at.is_some());
+        assert_eq!(follow_ups.matches.found, [1, 3]);
+        assert_eq!(follow_ups.unblocked, [2]);
+        assert_eq!(follow_ups.recurrences.len(), 1);
+        assert!(follow_ups.recurrences.iter().all(|&id| to_do
+            .get_task(id)
+            .is_some_and(|task| {
+                task.description == "Water the plants"
+                    && task.state == State::ToDo
+            })));
+    }
+
+    #[test]
+    fn rejects_tasks_that_werent_selected() {
+        assert_eq!(
+            error(
+                &[1],
+                "[[tasks]]\nid = 2\ndescription = \"A\"\nstate = \"ToDo\"\n\
+                 project = \"Docs\"\n"
+            ),
+            config().format("task_not_selected", &[("id", &2)])
+        );
+    }
+
+    #[test]
+    fn rejects_repeated_tasks() {
+        let task = "[[tasks]]\nid = 1\ndescription = \"A\"\n\
+                    state = \"ToDo\"\nproject = \"Docs\"\n";
+
+        assert_eq!(
+            error(&[1], &format!("{task}{task}")),
+            config().format("task_repeated", &[("id", &1)])
+        );
+    }
+
+    #[test]
+    fn rejects_tasks_that_no_longer_exist() {
+        assert_eq!(
+            error(
+                &[9],
+                "[[tasks]]\nid = 9\ndescription = \"A\"\nstate = \"ToDo\"\n\
+                 project = \"Docs\"\n"
+            ),
+            config().format("task_missing", &[("id", &9)])
+        );
+    }
+
+    #[test]
+    fn rejects_empty_descriptions() {
+        assert_eq!(
+            error(
+                &[1],
+                "[[tasks]]\nid = 1\ndescription = \"  \"\nstate = \"ToDo\"\n\
+                 project = \"Docs\"\n"
+            ),
+            config().format("task_without_description", &[("id", &1)])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_invalid_values() {
+        let task = "[[tasks]]\nid = 1\ndescription = \"A\"\nstate = \"ToDo\"\n\
+                    project = \"Docs\"\n";
+
+        assert!(apply(&[1], &format!("{task}priority = 1\n")).is_err());
+        assert!(apply(&[1], &format!("{task}estimate = \"soon\"\n")).is_err());
+        assert!(apply(&[1], &task.replace("ToDo", "Later")).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+use super::helpers::{bail_missing_tasks, strip_disabled_colors};
+use crate::{
+    cli::FocusTask,
+    config::{Configuration, Pomodoro},
+};
+use anstream::{print, println};
+use anyhow::bail;
+use chrono::{Local, Utc};
+use lib_tasker::{error::TaskerFailure, todos::ToDo};
+use owo_colors::OwoColorize;
+use ratatui::crossterm::{
+    cursor::MoveToColumn,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+use std::{
+    io::Write,
+    process::{Child, Stdio},
+    time::{Duration, Instant},
+};
+
+/// How often the countdown is redrawn while waiting for input.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    const fn minutes(self, config: &Configuration) -> u64 {
+        match self {
+            Self::Work => config.pomodoro.work.get(),
+            Self::ShortBreak => config.pomodoro.short_break.get(),
+            Self::LongBreak => config.pomodoro.long_break.get(),
+        }
+    }
+
+    /// Returns the phase following this one after the given number of
+    /// completed pomodoros: a break after working, long every
+    /// `long_break_every` pomodoros and short otherwise, and work after a
+    /// break.
+    const fn next(self, completed: usize, pomodoro: &Pomodoro) -> Self {
+        let every = pomodoro.long_break_every.get();
+
+        match self {
+            Self::Work if completed > 0 && completed % every == 0 => {
+                Self::LongBreak
+            }
+            Self::Work => Self::ShortBreak,
+            Self::ShortBreak | Self::LongBreak => Self::Work,
+        }
+    }
+
+    /// Name given to the notification command through `TASKER_PHASE`.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::ShortBreak => "short-break",
+            Self::LongBreak => "long-break",
+        }
+    }
+
+    /// Key of the phase's name in the message catalogues.
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Work => "phase_work",
+            Self::ShortBreak => "phase_short_break",
+            Self::LongBreak => "phase_long_break",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Finished,
+    Skipped,
+    Quit,
+}
+
+struct Focus<'a> {
+    config: &'a Configuration,
+    id: usize,
+    description: String,
+    completed: usize,
+    notifications: Vec<Child>,
+}
+
+/// Runs pomodoro cycles for the given Task, tracking time while working and
+/// logging every completed pomodoro on it.
+///
+/// # Errors
+///
+/// Returns an error if the Task doesn't exist, if another Task is being
+/// tracked, or if it failed to read input or to read or save the Task file.
+pub fn focus_on_task(
+    to_focus: &FocusTask,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let to_do = ToDo::get_to_do(&config.to_do_path)?;
+
+    let Some(task) = to_do.get_task(to_focus.task) else {
+        return bail_missing_tasks(
+            &TaskerFailure::TasksNotFound(vec![to_focus.task]),
+            config,
+        );
+    };
+
+    let mut focus = Focus {
+        config,
+        id: task.id,
+        description: task.description.clone(),
+        completed: 0,
+        notifications: Vec::new(),
+    };
+
+    focus.start_work()?;
+
+    terminal::enable_raw_mode()?;
+    let result = focus.run(to_focus.cycles);
+    terminal::disable_raw_mode()?;
+
+    for mut child in focus.notifications.drain(..) {
+        let _ = child.wait();
+    }
+
+    result?;
+
+    println!(
+        "{}: {}",
+        config
+            .text("pomodoros_completed")
+            .style(config.theme.success()),
+        focus.completed
+    );
+
+    Ok(())
+}
+
+impl Focus<'_> {
+    fn run(&mut self, cycles: Option<usize>) -> anyhow::Result<()> {
+        let mut phase = Phase::Work;
+
+        loop {
+            let outcome = self.countdown(phase)?;
+
+            if phase == Phase::Work {
+                self.finish_work(outcome == Outcome::Finished)?;
+            }
+
+            if outcome == Outcome::Quit
+                || cycles.is_some_and(|cycles| self.completed >= cycles)
+            {
+                self.notify("done");
+                return Ok(());
+            }
+
+            phase = phase.next(self.completed, &self.config.pomodoro);
+
+            if phase == Phase::Work {
+                self.start_work()?;
+            }
+
+            self.notify(phase.name());
+        }
+    }
+
+    /// Shows a countdown for the given phase until it ends or the user skips
+    /// it or quits.
+    fn countdown(&self, phase: Phase) -> anyhow::Result<Outcome> {
+        let length = Duration::from_secs(phase.minutes(self.config) * 60);
+        let end = Instant::now() + length;
+        let mut stdout = std::io::stdout();
+
+        let hint = self.config.text("focus_keys");
+
+        let title = match phase {
+            Phase::Work => format!(
+                "{} #{}",
+                self.config
+                    .text(phase.label())
+                    .style(self.config.theme.error())
+                    .bold(),
+                self.completed + 1
+            ),
+            Phase::ShortBreak | Phase::LongBreak => self
+                .config
+                .text(phase.label())
+                .style(self.config.theme.success())
+                .bold()
+                .to_string(),
+        };
+
+        loop {
+            let remaining = end.saturating_duration_since(Instant::now());
+            let seconds =
+                remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+            queue!(
+                stdout,
+                MoveToColumn(0),
+                Clear(ClearType::CurrentLine),
+                Print(strip_disabled_colors(format!(
+                    "{title} {:02}:{:02}  {}. {}  {}",
+                    seconds / 60,
+                    seconds % 60,
+                    self.id.style(self.config.theme.id()),
+                    self.description,
+                    hint.style(self.config.theme.muted())
+                )))
+            )?;
+            stdout.flush()?;
+
+            if remaining.is_zero() {
+                queue!(stdout, Print("\r\n"))?;
+                return Ok(Outcome::Finished);
+            }
+
+            if !event::poll(remaining.min(TICK_RATE))? {
+                continue;
+            }
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+
+                let outcome = match key.code {
+                    KeyCode::Char('c')
+                        if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        Outcome::Quit
+                    }
+                    KeyCode::Char('q') | KeyCode::Esc => Outcome::Quit,
+                    KeyCode::Char('s') => Outcome::Skipped,
+                    _ => continue,
+                };
+
+                queue!(stdout, Print("\r\n"))?;
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Moves the Task to `Doing` and starts tracking time for it, unless it's
+    /// already being tracked.
+    fn start_work(&self) -> anyhow::Result<()> {
+        let mut to_do = ToDo::get_to_do(&self.config.to_do_path)?;
+
+        let started = to_do.start_tracking(
+            self.id,
+            Local::now().date_naive(),
+            Utc::now(),
+        );
+
+        match started {
+            Ok(_) => {}
+            Err(TaskerFailure::AlreadyTracking(id)) if id == self.id => {}
+            Err(TaskerFailure::AlreadyTracking(id)) => bail!(
+                "{}",
+                self.config.format(
+                    "already_tracking",
+                    &[("id", &id.style(self.config.theme.id()))]
+                )
+            ),
+            Err(err) => bail_missing_tasks(&err, self.config)?,
+        }
+
+        to_do.save(&self.config.to_do_path)?;
+
+        Ok(())
+    }
+
+    /// Stops tracking time for the Task, logging a pomodoro on it if the work
+    /// phase was completed.
+    fn finish_work(&mut self, completed: bool) -> anyhow::Result<()> {
+        let mut to_do = ToDo::get_to_do(&self.config.to_do_path)?;
+        let now = Utc::now();
+
+        if completed {
+            if let Some(task) =
+                to_do.tasks.iter_mut().find(|task| task.id == self.id)
+            {
+                task.log_pomodoro(now);
+                self.completed += 1;
+            }
+        }
+
+        if to_do.tracked_task().is_some_and(|task| task.id == self.id) {
+            to_do.stop_tracking(now)?;
+        }
+
+        to_do.save(&self.config.to_do_path)?;
+
+        Ok(())
+    }
+
+    /// Runs the configured notification command, if any, without waiting for
+    /// it to finish.
+    fn notify(&mut self, phase: &str) {
+        let Some(command) = &self.config.pomodoro.notify_command else {
+            return;
+        };
+
+        let mut shell = if cfg!(windows) {
+            let mut shell = std::process::Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = std::process::Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+
+        let child = shell
+            .arg(command)
+            .env("TASKER_PHASE", phase)
+            .env("TASKER_TASK_ID", self.id.to_string())
+            .env("TASKER_TASK", &self.description)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        match child {
+            Ok(child) => self.notifications.push(child),
+            Err(err) => {
+                let message = self.config.text("notification_failed");
+
+                print!(
+                    "{}: {err}\r\n",
+                    message.style(self.config.theme.error())
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    fn phases(pomodoro: &Pomodoro, pomodoros: usize) -> Vec<Phase> {
+        let mut phase = Phase::Work;
+        let mut completed = 0;
+        let mut phases = Vec::new();
+
+        while completed < pomodoros {
+            if phase == Phase::Work {
+                completed += 1;
+            }
+
+            phase = phase.next(completed, pomodoro);
+            phases.push(phase);
+        }
+
+        phases
+    }
+
+    #[test]
+    fn takes_a_long_break_every_few_pomodoros() {
+        let pomodoro = Pomodoro::default();
+
+        assert_eq!(
+            phases(&pomodoro, 5),
+            [
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::LongBreak,
+                Phase::Work,
+                Phase::ShortBreak,
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_the_configured_cycle() {
+        let pomodoro = Pomodoro {
+            long_break_every: NonZeroUsize::new(2).expect("non-zero"),
+            ..Pomodoro::default()
+        };
+
+        assert_eq!(Phase::Work.next(1, &pomodoro), Phase::ShortBreak);
+        assert_eq!(Phase::Work.next(2, &pomodoro), Phase::LongBreak);
+        assert_eq!(Phase::Work.next(4, &pomodoro), Phase::LongBreak);
+        assert_eq!(Phase::ShortBreak.next(3, &pomodoro), Phase::Work);
+        assert_eq!(Phase::LongBreak.next(4, &pomodoro), Phase::Work);
+
+        let every_time = Pomodoro {
+            long_break_every: NonZeroUsize::MIN,
+            ..Pomodoro::default()
+        };
+
+        assert_eq!(Phase::Work.next(1, &every_time), Phase::LongBreak);
+    }
+
+    #[test]
+    fn skipping_the_first_pomodoro_takes_a_short_break() {
+        assert_eq!(
+            Phase::Work.next(0, &Pomodoro::default()),
+            Phase::ShortBreak
+        );
+    }
+}
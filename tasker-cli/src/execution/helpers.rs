@@ -1,137 +1,748 @@
+use super::table::push_table;
 use crate::{
-    cli::{ListTasks, SortTasks},
-    config::{Configuration, Language},
+    cli::{ListTasks, SortKey, SortTasks},
+    config::{Configuration, ListFormat},
 };
+use anstream::{
+    adapter::strip_str, eprintln, print, println, AutoStream, ColorChoice,
+};
+use anyhow::bail;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, Utc};
 use indexmap::IndexSet;
 use itertools::Itertools;
-use lib_tasker::todos::{State, Task, ToDo};
+use lib_tasker::{
+    error::TaskerFailure,
+    todos::{Filter, State, Task, ToDo},
+};
 use owo_colors::OwoColorize;
+use std::{cmp::Ordering, collections::HashSet, fmt::Write};
+use terminal_size::Width;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+/// Lists ids, joining runs of three or more consecutive ones into ranges like
+/// `3-8`.
 #[must_use]
-pub fn get_next_index(to_do: &ToDo) -> usize {
-    to_do.tasks.last().map_or(0, |last| last.id + 1)
+pub fn format_ids(ids: &[usize]) -> String {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+
+    for &id in ids {
+        match runs.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(id) => *end = id,
+            _ => runs.push((id, id)),
+        }
+    }
+
+    runs.iter()
+        .map(|&(start, end)| match end - start {
+            0 => start.to_string(),
+            1 => format!("{start}, {end}"),
+            _ => format!("{start}-{end}"),
+        })
+        .join(", ")
 }
 
-fn push_task(task: &Task, string: &mut String, config: &Configuration) {
-    string.push_str(&format!("{}. {}\n", task.id.purple(), task.description));
+/// Formats a duration as hours and minutes, e.g. `1h 05m`.
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
 
-    match config.language {
-        Language::English => match task.state {
-            State::ToDo => string.push_str(&format!("[{}] ", "To-Do".blue())),
-            State::Doing => {
-                string.push_str(&format!("[{}] ", "Doing".yellow()));
-            }
-            State::Done => string.push_str(&format!("[{}] ", "Done".green())),
-            State::Waiting => {
-                string.push_str(&format!("[{}] ", "Waiting".red()));
-            }
-        },
-        Language::Spanish => match task.state {
-            State::ToDo => {
-                string.push_str(&format!("[{}] ", "Por Hacer".blue()));
-            }
-            State::Doing => {
-                string.push_str(&format!("[{}] ", "Haciendo".yellow()));
+/// Returns the instant the given local day starts at.
+#[must_use]
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or_else(
+            || date.and_time(NaiveTime::MIN).and_utc(),
+            |start| start.with_timezone(&Utc),
+        )
+}
+
+/// Returns the width of the terminal, as set in `$COLUMNS` or reported by the
+/// terminal itself, defaulting to 80 columns.
+#[must_use]
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| {
+            terminal_size::terminal_size()
+                .map(|(Width(width), _)| usize::from(width))
+        })
+        .unwrap_or(80)
+}
+
+/// Removes colours from text written straight to standard output when they're
+/// disabled, which the `print` macros already do on their own.
+#[must_use]
+pub fn strip_disabled_colors(text: String) -> String {
+    if AutoStream::choice(&std::io::stdout()) == ColorChoice::Never {
+        strip_str(&text).to_string()
+    } else {
+        text
+    }
+}
+
+/// Returns the displayed width of text that may contain colour codes.
+#[must_use]
+pub fn plain_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut characters = text.chars();
+
+    while let Some(character) = characters.next() {
+        if character == '\u{1b}' {
+            characters.by_ref().find(|&character| character == 'm');
+        } else {
+            width += character.width().unwrap_or(0);
+        }
+    }
+
+    width
+}
+
+/// Shortens the given text to fit within `width` columns, ending it with an
+/// ellipsis if anything had to be cut.
+#[must_use]
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+
+    for character in text.chars() {
+        let character_width = character.width().unwrap_or(0);
+
+        if used + character_width + 1 > width {
+            break;
+        }
+
+        truncated.push(character);
+        used += character_width;
+    }
+
+    if width > 0 {
+        truncated.push('…');
+    }
+
+    truncated
+}
+
+/// Splits the given text into lines of at most `width` columns, breaking
+/// between words whenever possible.
+#[must_use]
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let separator = usize::from(!line.is_empty());
+
+        if line.width() + separator + word.width() <= width {
+            if !line.is_empty() {
+                line.push(' ');
             }
-            State::Done => string.push_str(&format!("[{}] ", "Hecho".green())),
-            State::Waiting => {
-                string.push_str(&format!("[{}] ", "Esperando".red()));
+
+            line.push_str(word);
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        for character in word.chars() {
+            if line.width() + character.width().unwrap_or(0) > width {
+                lines.push(std::mem::take(&mut line));
             }
-        },
+
+            line.push(character);
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Prints a warning listing the ids that didn't match any Task.
+pub fn report_missing_tasks(missing: &[usize], config: &Configuration) {
+    if missing.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{}: {}",
+        config
+            .plural("tasks_not_found", missing.len(), &[])
+            .style(config.theme.error()),
+        format_ids(missing)
+    );
+}
+
+/// Turns a failed Task lookup into a localized error.
+///
+/// # Errors
+///
+/// Always returns an error, either describing the missing Tasks or forwarding
+/// the given error if it has nothing to do with them.
+pub fn bail_missing_tasks<T>(
+    err: &TaskerFailure,
+    config: &Configuration,
+) -> anyhow::Result<T> {
+    let TaskerFailure::TasksNotFound(ids) = err else {
+        bail!("{err}");
+    };
+
+    if ids.is_empty() {
+        bail!(
+            "{}",
+            config.text("no_tasks_found").style(config.theme.error())
+        );
+    }
+
+    bail!(
+        "{}: {}",
+        config.text("no_tasks_found").style(config.theme.error()),
+        format_ids(ids)
+    )
+}
+
+fn push_task(
+    task: &Task,
+    to_do: &ToDo,
+    string: &mut String,
+    config: &Configuration,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+
+    let _ = writeln!(
+        string,
+        "{indent}{}. {}",
+        task.id.style(config.theme.id()),
+        task.description
+    );
+
+    let _ = write!(string, "{indent}[{}] ", state_name(task.state, config));
+
+    if let Some(due) = task.due {
+        let label = config.text("due");
+
+        let _ =
+            write!(string, "[{}: {due}] ", label.style(config.theme.label()));
+    }
+
+    if let Some(recurrence) = &task.recurrence {
+        let label = config.text("recurs");
+
+        let _ = write!(
+            string,
+            "[{}: {recurrence}] ",
+            label.style(config.theme.label())
+        );
+    }
+
+    if let Some(estimate) = task.estimate {
+        let label = config.text("estimate");
+
+        let _ = write!(
+            string,
+            "[{}: {estimate}] ",
+            label.style(config.theme.label())
+        );
+    }
+
+    let blockers = to_do.blockers(task);
+
+    if !blockers.is_empty() {
+        let blocked = config.text("blocked_by");
+
+        let _ = write!(
+            string,
+            "[{}: {}] ",
+            blocked.style(config.theme.error()),
+            format_ids(&blockers)
+        );
     }
 
     string.push_str("{ ");
     let tags = task.tags.iter().join(", ");
     string.push_str(&tags);
-    string.push_str(" }\n\n");
+    string.push_str(" }");
+
+    if !task.annotations.is_empty() {
+        let notes = config.plural("note_count", task.annotations.len(), &[]);
+
+        let _ = write!(string, " [{notes}]");
+    }
+
+    if let Some((done, total)) = to_do.subtask_progress(task.id) {
+        let progress = config.plural(
+            "subtasks_done",
+            total,
+            &[("done", &done), ("total", &total)],
+        );
+
+        let _ = write!(string, " ({})", progress.style(config.theme.label()));
+    }
+
+    string.push_str("\n\n");
 }
 
-pub fn list_to_dos(
-    to_do: ToDo,
-    config: &Configuration,
-    args: Option<ListTasks>,
+/// Collects the given Task followed by its shown subtasks within the same
+/// project, each with its depth below the given Task.
+fn collect_task_tree<'a>(
+    task: &'a Task,
+    to_do: &'a ToDo,
+    shown: &HashSet<usize>,
+    tree: &mut Vec<(&'a Task, usize)>,
+    depth: usize,
 ) {
+    tree.push((task, depth));
+
+    for child in to_do.children(task.id).filter(|child| {
+        child.project == task.project && shown.contains(&child.id)
+    }) {
+        collect_task_tree(child, to_do, shown, tree, depth + 1);
+    }
+}
+
+/// Whether a Task should be shown at the top level of its project, which is
+/// the case unless its parent is shown within the same project.
+fn is_project_root(task: &Task, to_do: &ToDo, shown: &HashSet<usize>) -> bool {
+    task.parent.is_none_or(|parent| {
+        !to_do.tasks.iter().any(|other| {
+            other.id == parent
+                && other.project == task.project
+                && shown.contains(&other.id)
+        })
+    })
+}
+
+/// Prints every detail of a Task, including its notes.
+pub fn show_task(task: &Task, to_do: &ToDo, config: &Configuration) {
+    let label = |key: &'static str| {
+        config.text(key).style(config.theme.label()).to_string()
+    };
+
     let mut output = String::new();
 
-    if let Some(options) = args {
-        let mut tasks = to_do.tasks;
-
-        if let Some(description) = options.description {
-            tasks.retain(|task| {
-                task.description
-                    .to_lowercase()
-                    .contains(&description.to_lowercase())
-            });
-        }
+    let _ = writeln!(
+        output,
+        "{}. {}",
+        task.id.style(config.theme.id()),
+        task.description.style(config.theme.emphasis())
+    );
+    let _ = writeln!(
+        output,
+        "{}: {}",
+        label("state"),
+        state_name(task.state, config)
+    );
+    let _ = writeln!(output, "{}: {}", label("project"), task.project);
+    let _ =
+        writeln!(output, "{}: {}", label("tags"), task.tags.iter().join(", "));
 
-        if let Some(state) = options.state {
-            tasks.retain(|task| task.state == state.into());
-        }
+    if let Some(parent) = task.parent {
+        let _ = writeln!(output, "{}: {parent}", label("parent"));
+    }
 
-        if let Some(tags) = options.tag {
-            let tags: IndexSet<String> = tags.into_iter().collect();
-            tasks.retain(|task| task.tags.intersection(&tags).count() > 0);
-        }
+    let children = to_do.children(task.id).map(|child| child.id).collect_vec();
+
+    if !children.is_empty() {
+        let _ = writeln!(
+            output,
+            "{}: {}",
+            label("subtasks"),
+            format_ids(&children)
+        );
+    }
+
+    if !task.depends_on.is_empty() {
+        let dependencies = task.depends_on.iter().copied().collect_vec();
 
-        if let Some(project) = options.project {
-            tasks.retain(|task| {
-                task.project
-                    .to_lowercase()
-                    .contains(&project.to_lowercase())
-            });
+        let _ = writeln!(
+            output,
+            "{}: {}",
+            label("depends_on"),
+            format_ids(&dependencies)
+        );
+    }
+
+    let blockers = to_do.blockers(task);
+
+    if !blockers.is_empty() {
+        let _ = writeln!(
+            output,
+            "{}: {}",
+            label("blocked_by"),
+            format_ids(&blockers)
+        );
+    }
+
+    if let Some(due) = task.due {
+        let _ = writeln!(output, "{}: {due}", label("due"));
+    }
+
+    if let Some(recurrence) = &task.recurrence {
+        let _ = writeln!(output, "{}: {recurrence}", label("recurs"));
+    }
+
+    if let Some(estimate) = task.estimate {
+        let _ = writeln!(output, "{}: {estimate}", label("estimate"));
+    }
+
+    if !task.pomodoros.is_empty() {
+        let _ = writeln!(
+            output,
+            "{}: {}",
+            label("pomodoros"),
+            task.pomodoros.len()
+        );
+    }
+
+    if !task.annotations.is_empty() {
+        let _ = writeln!(output, "{}:", label("notes"));
+
+        for annotation in &task.annotations {
+            let _ = writeln!(
+                output,
+                "  {} {}",
+                annotation
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .style(config.theme.muted()),
+                annotation.text
+            );
         }
+    }
 
-        if let Some(sort_options) = options.sort_by {
-            match sort_options {
-                SortTasks::Description => tasks.sort_unstable_by(|a, b| {
-                    a.description
+    println!("{output}");
+}
+
+/// Returns the localized and coloured name of a state.
+pub fn state_name(state: State, config: &Configuration) -> String {
+    let name = config.text(match state {
+        State::ToDo => "state_to_do",
+        State::Doing => "state_doing",
+        State::Waiting => "state_waiting",
+        State::Done => "state_done",
+    });
+
+    name.style(config.theme.state(state)).to_string()
+}
+
+/// Returns the Tasks matching the given filters, sorted as requested.
+#[must_use]
+pub fn filter_tasks(to_do: &ToDo, options: &ListTasks) -> Vec<Task> {
+    let mut tasks = to_do.tasks.clone();
+
+    if let Some(description) = &options.description {
+        tasks.retain(|task| {
+            task.description
+                .to_lowercase()
+                .contains(&description.to_lowercase())
+        });
+    }
+
+    if let Some(state) = options.state {
+        tasks.retain(|task| task.state == state.into());
+    }
+
+    if let Some(tags) = &options.tag {
+        let tags: IndexSet<String> = tags.iter().cloned().collect();
+        tasks.retain(|task| task.tags.intersection(&tags).count() > 0);
+    }
+
+    if let Some(project) = &options.project {
+        tasks.retain(|task| {
+            task.project
+                .to_lowercase()
+                .contains(&project.to_lowercase())
+        });
+    }
+
+    if options.blocked {
+        tasks.retain(|task| to_do.is_blocked(task));
+    }
+
+    if options.unblocked {
+        tasks.retain(|task| !to_do.is_blocked(task));
+    }
+
+    if options.unestimated {
+        tasks.retain(|task| task.estimate.is_none());
+    }
+
+    if let Some(filter) = &options.filter {
+        let today = Local::now().date_naive();
+        tasks.retain(|task| filter.matches(task, to_do, today));
+    }
+
+    sort_tasks(&mut tasks, &options.sort_by);
+
+    tasks
+}
+
+/// Sorts Tasks by each of the given keys in turn, keeping their order where
+/// they're all equal.
+pub fn sort_tasks(tasks: &mut [Task], keys: &[SortKey]) {
+    tasks.sort_by(|a, b| {
+        keys.iter().fold(Ordering::Equal, |ordering, key| {
+            ordering.then_with(|| {
+                let ordering = match key.field {
+                    SortTasks::Description => a
+                        .description
                         .to_lowercase()
-                        .cmp(&b.description.to_lowercase())
-                }),
-                SortTasks::Project => tasks.sort_unstable_by(|a, b| {
-                    a.project.to_lowercase().cmp(&b.project.to_lowercase())
-                }),
-                SortTasks::ID => tasks.sort_unstable_by(|a, b| a.id.cmp(&b.id)),
-                SortTasks::State => {
-                    tasks.sort_unstable_by(|a, b| a.state.cmp(&b.state));
+                        .cmp(&b.description.to_lowercase()),
+                    SortTasks::Project => {
+                        a.project.to_lowercase().cmp(&b.project.to_lowercase())
+                    }
+                    SortTasks::ID => a.id.cmp(&b.id),
+                    SortTasks::State => a.state.cmp(&b.state),
+                    // Unestimated Tasks go last either way.
+                    SortTasks::Estimate => match (a.estimate, b.estimate) {
+                        (Some(_), None) => return Ordering::Less,
+                        (None, Some(_)) => return Ordering::Greater,
+                        (a, b) => a.cmp(&b),
+                    },
+                };
+
+                if key.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
                 }
-            }
-        }
+            })
+        })
+    });
+}
 
-        tasks
-            .iter()
-            .for_each(|task| push_task(task, &mut output, config));
+/// Prints the Tasks matching the active context, narrowed down by the given
+/// filters if any, or grouped by project otherwise.
+pub fn list_to_dos(
+    to_do: &ToDo,
+    config: &Configuration,
+    args: Option<ListTasks>,
+    format: ListFormat,
+    context: &Filter,
+) {
+    let mut output = String::new();
+    let today = Local::now().date_naive();
+
+    if let Some(options) = args {
+        push_tracked_task(to_do, &mut output, config);
+
+        let mut tasks = filter_tasks(to_do, &options);
+        tasks.retain(|task| context.matches(task, to_do, today));
+
+        let tasks: Vec<(&Task, usize)> =
+            tasks.iter().map(|task| (task, 0)).collect();
+
+        push_tasks(&tasks, to_do, &mut output, config, format, true);
     } else {
-        match config.language {
-            Language::English => output.push_str(&format!(
-                "Hello, {}!\nHere's what you got for today:\n",
-                config.name
-            )),
-            Language::Spanish => output.push_str(&format!(
-                "¡Hola, {}!\nEsto es lo que tienes para hoy:\n",
-                config.name
-            )),
-        }
+        let _ = writeln!(
+            output,
+            "{}",
+            config.format("greeting", &[("name", &config.name)])
+        );
 
         output.push('\n');
+        push_tracked_task(to_do, &mut output, config);
+
+        let shown: HashSet<usize> = to_do
+            .tasks
+            .iter()
+            .filter(|task| context.matches(task, to_do, today))
+            .map(|task| task.id)
+            .collect();
 
         let projects = to_do
             .tasks
             .iter()
+            .filter(|task| shown.contains(&task.id))
             .unique_by(|task| &task.project)
             .map(|task| task.project.clone())
             .sorted();
 
         for project in projects {
-            output.push_str(&format!("{}\n\n", project.purple().underline()));
+            let _ = write!(
+                output,
+                "{}",
+                project.style(config.theme.project()).underline()
+            );
+
+            let (remaining, completed) = to_do.project_effort(&project);
+
+            if !remaining.is_empty() || !completed.is_empty() {
+                let effort = config.format(
+                    "project_effort",
+                    &[("remaining", &remaining), ("done", &completed)],
+                );
+
+                let _ =
+                    write!(output, " {}", effort.style(config.theme.muted()));
+            }
+
+            output.push_str("\n\n");
 
-            to_do
-                .tasks
-                .iter()
-                .filter(|task| task.project == project)
-                .for_each(|task| push_task(task, &mut output, config));
+            let mut tasks = Vec::new();
+
+            for task in to_do.tasks.iter().filter(|task| {
+                task.project == project
+                    && shown.contains(&task.id)
+                    && is_project_root(task, to_do, &shown)
+            }) {
+                collect_task_tree(task, to_do, &shown, &mut tasks, 0);
+            }
+
+            push_tasks(&tasks, to_do, &mut output, config, format, false);
+
+            if format != ListFormat::Blocks {
+                output.push('\n');
+            }
         }
     }
 
     print!("{output}");
 }
+
+/// Pushes Tasks along with their depth within their project's tree in the
+/// given layout. Compact lines only mention projects if `show_project` is set.
+fn push_tasks(
+    tasks: &[(&Task, usize)],
+    to_do: &ToDo,
+    output: &mut String,
+    config: &Configuration,
+    format: ListFormat,
+    show_project: bool,
+) {
+    match format {
+        ListFormat::Blocks => {
+            for &(task, depth) in tasks {
+                push_task(task, to_do, output, config, depth);
+            }
+        }
+        ListFormat::Compact => {
+            for &(task, depth) in tasks {
+                push_compact_task(task, output, config, depth, show_project);
+            }
+        }
+        ListFormat::Table => {
+            let tasks: Vec<&Task> =
+                tasks.iter().map(|&(task, _)| task).collect();
+            push_table(output, &tasks, &config.table_columns, config);
+        }
+    }
+}
+
+/// Pushes a Task on a single line, leaving out its notes and dependencies.
+pub fn push_compact_task(
+    task: &Task,
+    string: &mut String,
+    config: &Configuration,
+    depth: usize,
+    show_project: bool,
+) {
+    let _ = write!(
+        string,
+        "{}{}. [{}] {}",
+        "  ".repeat(depth),
+        task.id.style(config.theme.id()),
+        state_name(task.state, config),
+        task.description
+    );
+
+    if show_project {
+        let _ =
+            write!(string, " ({})", task.project.style(config.theme.project()));
+    }
+
+    for tag in &task.tags {
+        let _ =
+            write!(string, " {}", format!("#{tag}").style(config.theme.tag()));
+    }
+
+    if let Some(due) = task.due {
+        let due = config.format("due_on", &[("date", &due)]);
+
+        let _ = write!(string, " {}", due.style(config.theme.muted()));
+    }
+
+    string.push('\n');
+}
+
+/// Pushes a line with the Task being tracked and its elapsed time, if any.
+fn push_tracked_task(
+    to_do: &ToDo,
+    output: &mut String,
+    config: &Configuration,
+) {
+    let Some(task) = to_do.tracked_task() else {
+        return;
+    };
+
+    let now = Utc::now();
+    let elapsed = task
+        .time_log
+        .last()
+        .map_or_else(Duration::zero, |interval| {
+            interval.overlap(interval.start, now, now)
+        });
+
+    let tracking = config.text("tracking");
+
+    let _ = writeln!(
+        output,
+        "{}: {}. {} ({})\n",
+        tracking.style(config.theme.warning()).bold(),
+        task.id.style(config.theme.id()),
+        task.description,
+        format_duration(elapsed)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_with_an_ellipsis() {
+        assert_eq!(truncate("Write the docs", 8), "Write t…");
+        assert_eq!(truncate("Write the docs", 1), "…");
+    }
+
+    #[test]
+    fn keeps_text_that_fits_exactly() {
+        assert_eq!(truncate("Write the docs", 14), "Write the docs");
+        assert_eq!(truncate("日本語", 6), "日本語");
+    }
+
+    #[test]
+    fn truncates_wide_characters_by_their_width() {
+        assert_eq!(truncate("日本語のテキスト", 5), "日本…");
+        assert_eq!(truncate("日本語のテキスト", 6), "日本…");
+        assert_eq!(plain_width(&truncate("日本語のテキスト", 6)), 5);
+    }
+
+    #[test]
+    fn truncates_to_nothing_without_room() {
+        assert_eq!(truncate("Write the docs", 0), "");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn measures_text_without_colours() {
+        assert_eq!(plain_width(&"Docs".purple().underline().to_string()), 4);
+        assert_eq!(plain_width("日本"), 4);
+    }
+}
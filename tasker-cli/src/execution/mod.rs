@@ -1,18 +1,57 @@
+mod board;
+mod config;
+mod context;
+mod editor;
+mod focus;
 mod helpers;
+mod report;
+mod stats;
+mod table;
+mod timesheet;
+mod workspace;
 
 use crate::{
     cli::{
-        AddTasks, Cli, Command, DeleteTasks, EditTask, ListTasks, ToggleTasks,
+        exit_unknown_subcommand, AddTasks, AnnotateTask, Cli, Command,
+        ConfigCommand, DeleteTasks, EditTask, InitToDo, ListCommand, Report,
+        ShowStats, ShowTasks, StartTask, ToggleState, ToggleTasks,
     },
-    config::{Configuration, Language},
+    config::{Configuration, LoadError, Origin, Theme, DEFAULT_WORKSPACE},
+    i18n::catalogue,
+    tui::run_tui,
 };
+use anstream::{eprintln, println, ColorChoice};
 use anyhow::bail;
-use helpers::{get_next_index, list_to_dos};
+use board::print_board;
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{Local, Utc};
+use config::{
+    describe_diagnostic, describe_variable, manage_config, origin_label,
+    print_warnings,
+};
+use context::{context_defaults, context_filter, manage_context};
+use editor::edit_in_editor;
+use focus::focus_on_task;
+use helpers::{
+    bail_missing_tasks, format_duration, format_ids, list_to_dos,
+    report_missing_tasks, show_task,
+};
 use lib_tasker::{
-    io::get_project_directories,
-    todos::{State, Task, ToDo},
+    error::TaskerFailure,
+    io::{
+        get_project_directories, LOCAL_TO_DO_DIRECTORY, LOCAL_TO_DO_FILE,
+        TO_DO_FILE_NAME,
+    },
+    statistics::Statistics,
+    todos::{Filter, Task, ToDo, Transition},
 };
 use owo_colors::OwoColorize;
+use report::{print_activity, print_burndown, print_custom_report};
+use stats::print_stats;
+use timesheet::{default_since, print_timesheet};
+use workspace::{
+    list_all_workspaces, list_workspaces, switch_workspace, unknown_workspace,
+};
 
 /// Executes the application.
 ///
@@ -20,85 +59,406 @@ use owo_colors::OwoColorize;
 ///
 /// Returns an error if the execution of the application failed at any point.
 pub fn execute_application(cli: Cli) -> anyhow::Result<()> {
-    let configuration = if let Some(path) = cli.config_file {
-        Configuration::from_given_file(&path)?
-    } else {
-        let to_do_path = if let Some(path) = cli.todo_file {
-            path
-        } else {
-            ToDo::get_default_to_do_path()?
-        };
+    ColorChoice::from(cli.color).write_global();
 
-        Configuration::new(&to_do_path)?
-    };
+    // The doctor reports the problems of the configuration file itself.
+    let doctor =
+        matches!(cli.command, Some(Command::Config(ConfigCommand::Doctor(_))));
+
+    let configuration = load_configuration(&cli, doctor)?;
+
+    if !doctor {
+        print_warnings(&configuration);
+    }
 
     match cli.command {
-        Some(Command::Add(add)) => add_tasks(add, &configuration)?,
+        Some(Command::Add(add)) => {
+            let context = context_filter(&configuration)?;
+
+            add_tasks(add, &configuration, &context)?;
+        }
+        Some(Command::Annotate(annotate)) => {
+            annotate_task(&annotate, &configuration)?;
+        }
+        Some(Command::Board(mut board)) => {
+            let to_do = ToDo::get_to_do(&configuration.to_do_path)?;
+            let context = context_filter(&configuration)?;
+            board.filter =
+                Some(context.and(board.filter.unwrap_or(Filter::Any)));
+
+            print_board(&to_do, &configuration, &board);
+        }
         Some(Command::Clean) => clean_completed_tasks(&configuration)?,
+        Some(Command::Config(command)) => {
+            let path = config_path(cli.config_file)?;
+
+            manage_config(&command, &path, &configuration)?;
+        }
+        Some(Command::Context(command)) => {
+            let path = config_path(cli.config_file)?;
+
+            manage_context(&command, &path, &configuration)?;
+        }
         Some(Command::Delete(delete)) => delete_tasks(&delete, &configuration)?,
-        Some(Command::Edit(edit)) => edit_task(edit, &configuration)?,
+        Some(Command::Edit(edit)) => edit_task(&edit, &configuration)?,
+        Some(Command::Focus(focus)) => focus_on_task(&focus, &configuration)?,
         Some(Command::List(list)) => list_tasks(list, &configuration)?,
-        Some(Command::Paths) => get_paths()?,
+        Some(Command::ListWorkspaces) => list_workspaces(&configuration),
+        Some(Command::Init(init)) => init_to_do(&init, &configuration)?,
+        Some(Command::Paths) => {
+            get_paths(&configuration, cli.config_file.as_deref())?;
+        }
+        Some(Command::Report(report)) => {
+            let to_do = ToDo::get_to_do(&configuration.to_do_path)?;
+
+            match report {
+                Report::Burndown(burndown) => {
+                    print_burndown(&to_do, &configuration, &burndown)?;
+                }
+                Report::Activity(activity) => {
+                    print_activity(&to_do, &configuration, &activity)?;
+                }
+                Report::Custom(args) => {
+                    check_report(&["report"], &args, &configuration);
+                    let context = context_filter(&configuration)?;
+
+                    run_custom_report(&to_do, &configuration, &args, &context)?;
+                }
+            }
+        }
+        Some(Command::Show(show)) => show_tasks(&show, &configuration)?,
+        Some(Command::Start(start)) => start_task(&start, &configuration)?,
+        Some(Command::Stats(stats)) => show_stats(&stats, &configuration)?,
+        Some(Command::Stop) => stop_tracking(&configuration)?,
+        Some(Command::Switch(switch)) => {
+            let path = config_path(cli.config_file)?;
+
+            switch_workspace(&switch, &path, &configuration)?;
+        }
+        Some(Command::Timesheet(timesheet)) => {
+            let to_do = ToDo::get_to_do(&configuration.to_do_path)?;
+            let today = Local::now().date_naive();
+
+            print_timesheet(
+                &to_do,
+                &configuration,
+                timesheet.since.unwrap_or_else(|| default_since(today)),
+                timesheet.until.unwrap_or(today),
+            );
+        }
         Some(Command::Toggle(toggle)) => toggle_tasks(&toggle, &configuration)?,
-        None => {
+        Some(Command::Tui) => run_tui(&configuration)?,
+        Some(Command::Custom(args)) => {
+            check_report(&[], &args, &configuration);
             let to_do = ToDo::get_to_do(&configuration.to_do_path)?;
+            let context = context_filter(&configuration)?;
 
-            list_to_dos(to_do, &configuration, None);
+            run_custom_report(&to_do, &configuration, &args, &context)?;
+        }
+        None => {
+            let to_do = ToDo::get_to_do(&configuration.to_do_path)?;
+            let context = context_filter(&configuration)?;
+
+            list_to_dos(
+                &to_do,
+                &configuration,
+                None,
+                configuration.list_format,
+                &context,
+            );
         }
     }
 
     Ok(())
 }
 
-fn add_tasks(to_add: AddTasks, config: &Configuration) -> anyhow::Result<()> {
+/// Loads the configuration, falling back to the built-in one for the
+/// `config` subcommands that can fix a broken configuration file.
+fn load_configuration(
+    cli: &Cli,
+    doctor: bool,
+) -> anyhow::Result<Configuration> {
+    let diagnostic = match Configuration::load(
+        cli.config_file.as_deref(),
+        cli.todo_file.as_deref(),
+        cli.workspace.as_deref(),
+    ) {
+        Ok(configuration) => return Ok(configuration),
+        Err(LoadError::Failure(err)) => return Err(err.into()),
+        Err(LoadError::Invalid(diagnostic)) => diagnostic,
+        Err(LoadError::UnknownWorkspace(name)) => {
+            return load_default_workspace(cli, &name);
+        }
+        Err(LoadError::InvalidVariable {
+            variable,
+            value,
+            reason,
+        }) => bail!(
+            "{}",
+            describe_variable(
+                &variable,
+                &value,
+                &reason,
+                catalogue(Configuration::peek_language(
+                    cli.config_file.as_deref(),
+                    cli.workspace.as_deref()
+                ))
+            )
+        ),
+    };
+
+    let language = Configuration::peek_language(
+        cli.config_file.as_deref(),
+        cli.workspace.as_deref(),
+    );
+    let catalogue = catalogue(language);
+    let message = describe_diagnostic(&diagnostic, catalogue);
+
+    if !matches!(
+        &cli.command,
+        Some(Command::Config(command)) if command.fixes_file()
+    ) {
+        bail!("{message}");
+    }
+
+    if !doctor {
+        eprintln!(
+            "{}: {message}\n{}",
+            catalogue
+                .text("diagnostic_warning")
+                .style(Theme::default().warning()),
+            catalogue.text("config_fallback")
+        );
+    }
+
+    Ok(Configuration::fallback(language))
+}
+
+/// Loads the configuration with the default workspace instead of the
+/// selected one, which doesn't exist, for the commands that can select
+/// another.
+fn load_default_workspace(
+    cli: &Cli,
+    name: &str,
+) -> anyhow::Result<Configuration> {
+    let configuration = match Configuration::load(
+        cli.config_file.as_deref(),
+        cli.todo_file.as_deref(),
+        Some(DEFAULT_WORKSPACE),
+    ) {
+        Ok(configuration) => configuration,
+        Err(LoadError::Failure(err)) => return Err(err.into()),
+        Err(LoadError::Invalid(diagnostic)) => bail!(
+            "{}",
+            describe_diagnostic(
+                &diagnostic,
+                catalogue(Configuration::peek_language(
+                    cli.config_file.as_deref(),
+                    cli.workspace.as_deref()
+                ))
+            )
+        ),
+        Err(LoadError::UnknownWorkspace(name)) => bail!("{name}"),
+        Err(LoadError::InvalidVariable {
+            variable,
+            value,
+            reason,
+        }) => bail!(
+            "{}",
+            describe_variable(
+                &variable,
+                &value,
+                &reason,
+                catalogue(Configuration::peek_language(
+                    cli.config_file.as_deref(),
+                    cli.workspace.as_deref()
+                ))
+            )
+        ),
+    };
+
+    let message = unknown_workspace(name, &configuration);
+
+    let selects_another = match &cli.command {
+        Some(Command::Switch(_) | Command::ListWorkspaces) => true,
+        Some(Command::Config(command)) => command.fixes_file(),
+        _ => false,
+    };
+
+    if !selects_another {
+        bail!("{message}");
+    }
+
+    eprintln!(
+        "{}: {message}",
+        configuration
+            .text("diagnostic_warning")
+            .style(configuration.theme.warning())
+    );
+
+    Ok(configuration)
+}
+
+/// Returns the configuration file given with `--config-file`, or the user
+/// one.
+fn config_path(
+    config_file: Option<Utf8PathBuf>,
+) -> anyhow::Result<Utf8PathBuf> {
+    match config_file {
+        Some(path) => Ok(path),
+        None => Ok(Configuration::get_default_path()?),
+    }
+}
+
+/// Exits with clap's error for unknown subcommands unless the external
+/// subcommand given within the one at the given path names a custom report.
+fn check_report(path: &[&str], args: &[String], config: &Configuration) {
+    let name = args.first().map_or("", String::as_str);
+
+    if !config.reports.contains_key(name) {
+        exit_unknown_subcommand(path, name, config);
+    }
+}
+
+/// Runs a custom report given as an external subcommand, with its name first
+/// and any extra filter terms after it.
+fn run_custom_report(
+    to_do: &ToDo,
+    config: &Configuration,
+    args: &[String],
+    context: &Filter,
+) -> anyhow::Result<()> {
+    let (name, terms) = args
+        .split_first()
+        .map_or(("", &[][..]), |(name, terms)| (name.as_str(), terms));
+
+    print_custom_report(to_do, config, name, terms, context)
+}
+
+/// Adds Tasks, giving them the project and tags of the active context unless
+/// others are given.
+fn add_tasks(
+    to_add: AddTasks,
+    config: &Configuration,
+    context: &Filter,
+) -> anyhow::Result<()> {
     let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
-    let mut next_index = get_next_index(&to_do);
+    let mut next_index = to_do.next_id();
+
+    let parent_project = match to_add.parent {
+        Some(parent) => {
+            if let Err(err) = to_do.match_ids(&[parent]) {
+                bail_missing_tasks(&err, config)?;
+            }
 
-    match to_add.project {
-        Some(project) => {
-            to_do
-                .tasks
-                .extend(to_add.descriptions.into_iter().map(|desc| {
-                    let index = next_index;
-                    next_index += 1;
-
-                    Task::create(desc)
-                        .id(index)
-                        .project(project.clone())
-                        .tags(to_add.tag.clone().unwrap_or_default())
-                        .build()
-                }));
-        }
-        None => {
             to_do
                 .tasks
-                .extend(to_add.descriptions.into_iter().map(|desc| {
-                    let index = next_index;
-                    next_index += 1;
-
-                    Task::create(desc)
-                        .id(index)
-                        .tags(to_add.tag.clone().unwrap_or_default())
-                        .build()
-                }));
+                .iter()
+                .find(|task| task.id == parent)
+                .map(|task| task.project.clone())
         }
-    }
+        None => None,
+    };
 
-    match to_do.save(&config.to_do_path) {
-        Ok(()) => match config.language {
-            Language::English => println!("{}", "Added Tasks".green()),
-            Language::Spanish => {
-                println!("{}", "Tareas añadidas".green());
+    let (project, tags) = context_defaults(
+        context,
+        to_add.project.or(parent_project),
+        to_add.tag,
+    );
+    let first_index = next_index;
+    let now = Local::now();
+    let today = now.date_naive();
+
+    to_do
+        .tasks
+        .extend(to_add.descriptions.into_iter().map(|desc| {
+            let index = next_index;
+            next_index += 1;
+
+            let mut task = Task::create(desc);
+            task.id(index)
+                .tags(tags.clone())
+                .created_at(now.with_timezone(&Utc));
+
+            if let Some(project) = &project {
+                task.project(project.clone());
             }
-        },
-        Err(err) => match config.language {
-            Language::English => {
-                bail!("Failed to save Task file: {}", err.red())
+
+            if let Some(parent) = to_add.parent {
+                task.parent(parent);
+            }
+
+            if let Some(recurrence) = &to_add.recur {
+                task.due(recurrence.first_on_or_after(today))
+                    .recurrence(recurrence.clone());
+            }
+
+            if let Some(estimate) = to_add.estimate {
+                task.estimate(estimate);
             }
-            Language::Spanish => {
-                bail!("No se pudo guardar archivo de Tareas: {}", err.red())
+
+            task.build()
+        }));
+
+    if let Some(depends) = &to_add.depends {
+        let dependencies = to_do.resolve_ids(depends);
+
+        for id in first_index..next_index {
+            if let Err(err) =
+                to_do.set_dependencies(id, dependencies.iter().copied())
+            {
+                bail_missing_tasks(&err, config)?;
             }
-        },
+        }
+    }
+
+    match to_do.save(&config.to_do_path) {
+        Ok(()) => {
+            println!(
+                "{}",
+                config.text("added_tasks").style(config.theme.success())
+            );
+        }
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
+    }
+
+    Ok(())
+}
+
+fn annotate_task(
+    to_annotate: &AnnotateTask,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
+
+    match to_do
+        .tasks
+        .iter_mut()
+        .find(|task| task.id == to_annotate.task)
+    {
+        Some(task) => task.annotate(to_annotate.text.join(" "), Utc::now()),
+        None => bail_missing_tasks(
+            &TaskerFailure::TasksNotFound(vec![to_annotate.task]),
+            config,
+        )?,
+    }
+
+    match to_do.save(&config.to_do_path) {
+        Ok(()) => {
+            println!(
+                "{}",
+                config.text("added_note").style(config.theme.info())
+            );
+        }
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
     }
 
     Ok(())
@@ -107,25 +467,20 @@ fn add_tasks(to_add: AddTasks, config: &Configuration) -> anyhow::Result<()> {
 fn clean_completed_tasks(config: &Configuration) -> anyhow::Result<()> {
     let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
 
-    to_do.tasks.retain(|task| task.state != State::Done);
+    to_do.clean_completed();
 
     match to_do.save(&config.to_do_path) {
-        Ok(()) => match config.language {
-            Language::English => {
-                println!("{}", "Cleaned completed tasks".purple());
-            }
-            Language::Spanish => {
-                println!("{}", "Se limpiaron las Tareas completadas".purple());
-            }
-        },
-        Err(err) => match config.language {
-            Language::English => {
-                bail!("Failed to save Task file: {}", err.red())
-            }
-            Language::Spanish => {
-                bail!("No se pudo guardar archivo de Tareas: {}", err.red())
-            }
-        },
+        Ok(()) => {
+            println!(
+                "{}",
+                config.text("cleaned_tasks").style(config.theme.success())
+            );
+        }
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
     }
 
     Ok(())
@@ -137,90 +492,358 @@ fn delete_tasks(
 ) -> anyhow::Result<()> {
     let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
 
-    to_do
-        .tasks
-        .retain(|task| !to_delete.tasks.contains(&task.id));
+    let ids = to_do.resolve_ids(&to_delete.tasks);
+
+    let matches = match to_do.delete_tasks(&ids) {
+        Ok(matches) => matches,
+        Err(err) => bail_missing_tasks(&err, config)?,
+    };
 
     match to_do.save(&config.to_do_path) {
-        Ok(()) => match config.language {
-            Language::English => println!("{}", "Deleted Tasks".red()),
-            Language::Spanish => {
-                println!("{}", "Tareas eliminadas".red());
-            }
-        },
-        Err(err) => match config.language {
-            Language::English => {
-                bail!("Failed to save Task file: {}", err.red())
-            }
-            Language::Spanish => {
-                bail!("No se pudo guardar archivo de Tareas: {}", err.red())
-            }
-        },
+        Ok(()) => println!(
+            "{}: {}",
+            config
+                .plural("deleted_tasks", matches.found.len(), &[])
+                .style(config.theme.error()),
+            format_ids(&matches.found)
+        ),
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
     }
 
+    report_missing_tasks(&matches.missing, config);
+
     Ok(())
 }
 
-fn edit_task(to_edit: EditTask, config: &Configuration) -> anyhow::Result<()> {
+fn edit_task(to_edit: &EditTask, config: &Configuration) -> anyhow::Result<()> {
     let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
 
-    match to_do.tasks.iter_mut().find(|task| task.id == to_edit.task) {
-        Some(task) => {
-            if let Some(description) = to_edit.description {
-                task.description = description;
-            }
+    let ids = to_do.resolve_ids(&to_edit.tasks);
 
-            if let Some(project) = to_edit.project {
-                task.project = project;
-            }
+    let mut matches = match to_do.match_ids(&ids) {
+        Ok(matches) => matches,
+        Err(err) => bail_missing_tasks(&err, config)?,
+    };
 
-            if let Some(state) = to_edit.state {
-                task.state = state.into();
-            }
+    let mut follow_ups = Transition::default();
+
+    if to_edit.interactive {
+        follow_ups = edit_in_editor(&mut to_do, &matches.found, config)?;
+        matches.found.clone_from(&follow_ups.matches.found);
 
-            if let Some(tags) = to_edit.tags {
-                task.replace_tags(tags);
+        if matches.found.is_empty() {
+            println!(
+                "{}",
+                config.text("no_changes").style(config.theme.info())
+            );
+
+            report_missing_tasks(&matches.missing, config);
+
+            return Ok(());
+        }
+    }
+
+    for task in to_do
+        .tasks
+        .iter_mut()
+        .filter(|task| matches.found.contains(&task.id))
+    {
+        if let Some(description) = &to_edit.description {
+            task.description.clone_from(description);
+        }
+
+        if let Some(project) = &to_edit.project {
+            task.project.clone_from(project);
+        }
+
+        if let Some(tags) = &to_edit.tags {
+            task.replace_tags(tags);
+        }
+
+        if let Some(estimate) = to_edit.estimate {
+            task.estimate = estimate;
+        }
+    }
+
+    if let Some(depends) = &to_edit.depends {
+        let dependencies = to_do.resolve_ids(depends);
+
+        for &id in &matches.found {
+            if let Err(err) =
+                to_do.set_dependencies(id, dependencies.iter().copied())
+            {
+                bail_missing_tasks(&err, config)?;
             }
         }
-        None => match config.language {
-            Language::English => bail!("Task doesn't exist".red()),
-            Language::Spanish => bail!("Tarea no existe".red()),
-        },
+    }
+
+    if let Some(state) = to_edit.state {
+        let now = Local::now();
+        let transition = to_do.transition(
+            &matches.found,
+            state.into(),
+            now.date_naive(),
+            now.with_timezone(&Utc),
+        )?;
+
+        follow_ups.unblocked.extend(transition.unblocked);
+        follow_ups.recurrences.extend(transition.recurrences);
     }
 
     match to_do.save(&config.to_do_path) {
-        Ok(()) => match config.language {
-            Language::English => println!("{}", "Edited Task".blue()),
-            Language::Spanish => println!("{}", "Tarea editada".blue()),
-        },
-        Err(err) => match config.language {
-            Language::English => {
-                bail!("Failed to save Task file: {}", err.red())
-            }
-            Language::Spanish => {
-                bail!("No se pudo guardar archivo de Tareas: {}", err.red())
-            }
-        },
+        Ok(()) => println!(
+            "{}: {}",
+            config.text("edited_tasks").style(config.theme.info()),
+            format_ids(&matches.found)
+        ),
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
     }
 
+    report_follow_ups(&follow_ups.unblocked, &follow_ups.recurrences, config);
+    report_missing_tasks(&matches.missing, config);
+
     Ok(())
 }
 
 fn list_tasks(
-    to_list: ListTasks,
+    mut to_list: ListCommand,
     config: &Configuration,
 ) -> anyhow::Result<()> {
+    let context = context_filter(config)?;
+
+    if to_list.all_workspaces {
+        to_list.options.filter =
+            Some(context.and(to_list.options.filter.unwrap_or(Filter::Any)));
+
+        return list_all_workspaces(&to_list.options, config);
+    }
+
     let to_do = ToDo::get_to_do(&config.to_do_path)?;
-    list_to_dos(to_do, config, Some(to_list));
+    let format = to_list.format.unwrap_or(config.list_format);
+    list_to_dos(&to_do, config, Some(to_list.options), format, &context);
+
+    Ok(())
+}
+
+/// Creates an empty Tasks file for the current directory, as a `.tasker.ron`
+/// file or in a `.tasker` directory.
+fn init_to_do(init: &InitToDo, config: &Configuration) -> anyhow::Result<()> {
+    let current = Utf8PathBuf::try_from(std::env::current_dir()?)?;
+
+    for existing in [
+        current.join(LOCAL_TO_DO_FILE),
+        current.join(LOCAL_TO_DO_DIRECTORY),
+    ] {
+        if existing.exists() {
+            bail!("{}", config.format("init_exists", &[("path", &existing)]));
+        }
+    }
+
+    let path = if init.directory {
+        let directory = current.join(LOCAL_TO_DO_DIRECTORY);
+        std::fs::create_dir(&directory)?;
+
+        directory.join(TO_DO_FILE_NAME)
+    } else {
+        current.join(LOCAL_TO_DO_FILE)
+    };
+
+    match ToDo::default().save(&path) {
+        Ok(()) => println!(
+            "{}: {path}",
+            config.text("init_created").style(config.theme.success())
+        ),
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
+    }
 
     Ok(())
 }
 
-fn get_paths() -> anyhow::Result<()> {
+/// Prints the directories of the application, the configuration files in use
+/// and which Tasks file is used and why.
+fn get_paths(
+    config: &Configuration,
+    config_file: Option<&Utf8Path>,
+) -> anyhow::Result<()> {
     let paths = get_project_directories()?;
+    let label = |key| config.text(key).style(config.theme.label()).to_string();
+
+    println!(
+        "{}: {}",
+        label("paths_config"),
+        paths.config_dir().display()
+    );
+    println!("{}: {}", label("paths_data"), paths.data_dir().display());
+
+    let files = Configuration::files(config_file);
+
+    if !files.is_empty() {
+        println!("{}:", label("paths_config_files"));
 
-    println!("Config path: {}", paths.config_dir().display());
-    println!("Data path: {}", paths.data_dir().display());
+        for file in &files {
+            println!("  {}", origin_label(file, config));
+        }
+    }
+
+    let reason = match config.origin("to_do_path") {
+        Origin::Default => config.text("to_do_reason_default").to_string(),
+        Origin::System(path) | Origin::User(path) | Origin::Local(path) => {
+            config.format("to_do_reason_file", &[("path", &path)])
+        }
+        Origin::Environment(variable) => config
+            .format("to_do_reason_environment", &[("variable", &variable)]),
+        Origin::Discovered => {
+            config.text("to_do_reason_discovered").to_string()
+        }
+        Origin::Workspace(name) => {
+            config.format("to_do_reason_workspace", &[("name", &name)])
+        }
+        Origin::Flag(flag) => {
+            config.format("to_do_reason_flag", &[("flag", &flag)])
+        }
+    };
+
+    println!(
+        "{}: {}",
+        label("paths_workspace"),
+        config.active_workspace()
+    );
+    println!("{}: {}", label("paths_to_do_file"), config.to_do_path);
+    println!("  {}", reason.style(config.theme.info()));
+
+    Ok(())
+}
+
+fn show_tasks(
+    to_show: &ShowTasks,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let to_do = ToDo::get_to_do(&config.to_do_path)?;
+
+    let ids = to_do.resolve_ids(&to_show.tasks);
+
+    let matches = match to_do.match_ids(&ids) {
+        Ok(matches) => matches,
+        Err(err) => bail_missing_tasks(&err, config)?,
+    };
+
+    for task in matches.found.iter().filter_map(|&id| to_do.get_task(id)) {
+        show_task(task, &to_do, config);
+    }
+
+    report_missing_tasks(&matches.missing, config);
+
+    Ok(())
+}
+
+fn start_task(
+    to_start: &StartTask,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
+
+    let started = to_do.start_tracking(
+        to_start.task,
+        Local::now().date_naive(),
+        Utc::now(),
+    );
+
+    match started {
+        Ok(_) => {}
+        Err(TaskerFailure::AlreadyTracking(id)) => bail!(
+            "{}",
+            config.format(
+                "already_tracking",
+                &[("id", &id.style(config.theme.id()))]
+            )
+        ),
+        Err(err) => bail_missing_tasks(&err, config)?,
+    }
+
+    match to_do.save(&config.to_do_path) {
+        Ok(()) => println!(
+            "{}: {}",
+            config
+                .text("started_tracking")
+                .style(config.theme.warning()),
+            to_start.task.style(config.theme.id())
+        ),
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
+    }
+
+    Ok(())
+}
+
+fn show_stats(
+    options: &ShowStats,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let to_do = ToDo::get_to_do(&config.to_do_path)?;
+    let stats = Statistics::compute(
+        &to_do,
+        Local::now().date_naive(),
+        &Local,
+        options.weeks,
+        options.oldest,
+    );
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print_stats(&stats, config);
+    }
+
+    Ok(())
+}
+
+fn stop_tracking(config: &Configuration) -> anyhow::Result<()> {
+    let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
+
+    let Ok(id) = to_do.stop_tracking(Utc::now()) else {
+        bail!("{}", config.text("no_task_is_being_tracked"))
+    };
+
+    let elapsed = to_do
+        .get_task(id)
+        .and_then(|task| task.time_log.last())
+        .and_then(|interval| {
+            interval
+                .end
+                .map(|end| end.signed_duration_since(interval.start))
+        })
+        .unwrap_or_default();
+
+    match to_do.save(&config.to_do_path) {
+        Ok(()) => println!(
+            "{}: {} ({})",
+            config
+                .text("stopped_tracking")
+                .style(config.theme.warning()),
+            id.style(config.theme.id()),
+            format_duration(elapsed)
+        ),
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
+    }
 
     Ok(())
 }
@@ -231,30 +854,85 @@ fn toggle_tasks(
 ) -> anyhow::Result<()> {
     let mut to_do = ToDo::get_to_do(&config.to_do_path)?;
 
-    to_do
-        .tasks
-        .iter_mut()
-        .filter(|task| to_toggle.tasks.contains(&task.id))
-        .for_each(|task| task.change_state(to_toggle.state.into()));
+    let ids = to_do.resolve_ids(&to_toggle.tasks);
+
+    let matches = match to_do.match_ids(&ids) {
+        Ok(matches) => matches,
+        Err(err) => bail_missing_tasks(&err, config)?,
+    };
+
+    let mut open_subtasks = if to_toggle.state == ToggleState::Done {
+        to_do.open_descendants(&matches.found)
+    } else {
+        Vec::new()
+    };
+    open_subtasks.retain(|id| !matches.found.contains(id));
+
+    let mut targets = matches.found.clone();
+
+    if to_toggle.cascade {
+        targets.extend(&open_subtasks);
+    }
+
+    let now = Local::now();
+    let transition = to_do.transition(
+        &targets,
+        to_toggle.state.into(),
+        now.date_naive(),
+        now.with_timezone(&Utc),
+    )?;
 
     match to_do.save(&config.to_do_path) {
-        Ok(()) => match config.language {
-            Language::English => {
-                println!("{}", "State changed".yellow());
-            }
-            Language::Spanish => {
-                println!("{}", "Estado cambiado".yellow());
-            }
-        },
-        Err(err) => match config.language {
-            Language::English => {
-                bail!("Failed to save Task file: {}", err.red())
-            }
-            Language::Spanish => {
-                bail!("No se pudo guardar archivo de Tareas: {}", err.red())
-            }
-        },
+        Ok(()) => {
+            println!(
+                "{}: {}",
+                config.text("state_changed").style(config.theme.warning()),
+                format_ids(&transition.matches.found)
+            );
+        }
+        Err(err) => bail!(
+            "{}: {}",
+            config.text("save_failed"),
+            err.style(config.theme.error())
+        ),
+    }
+
+    if !to_toggle.cascade && !open_subtasks.is_empty() {
+        eprintln!(
+            "{}: {}",
+            config.text("open_subtasks").style(config.theme.warning()),
+            format_ids(&open_subtasks)
+        );
     }
 
+    report_follow_ups(&transition.unblocked, &transition.recurrences, config);
+    report_missing_tasks(&matches.missing, config);
+
     Ok(())
 }
+
+/// Reports the Tasks that were unblocked and the occurrences that were added
+/// after completing others.
+fn report_follow_ups(
+    unblocked: &[usize],
+    recurrences: &[usize],
+    config: &Configuration,
+) {
+    if !unblocked.is_empty() {
+        println!(
+            "{}: {}",
+            config.text("unblocked_tasks").style(config.theme.success()),
+            format_ids(unblocked)
+        );
+    }
+
+    if !recurrences.is_empty() {
+        println!(
+            "{}: {}",
+            config
+                .text("recurrences_added")
+                .style(config.theme.success()),
+            format_ids(recurrences)
+        );
+    }
+}
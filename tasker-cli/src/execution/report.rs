@@ -0,0 +1,360 @@
+use super::{
+    helpers::{sort_tasks, state_name, terminal_width},
+    table::push_table,
+};
+use crate::{
+    cli::{ActivityReport, BurndownReport, ReportPeriod, SortKey},
+    config::{Configuration, GroupBy},
+};
+use anstream::{print, println};
+use anyhow::bail;
+use chrono::{Days, Local, NaiveDate};
+use indexmap::IndexMap;
+use lib_tasker::{
+    statistics::{burndown, completions},
+    todos::{Filter, Task, ToDo},
+};
+use owo_colors::OwoColorize;
+use std::fmt::Write;
+
+/// Height of the burndown chart, in rows.
+const CHART_HEIGHT: usize = 10;
+
+/// Blocks filling a cell from the bottom, in eighths.
+const VERTICAL_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Blocks filling a cell from the left, in eighths.
+const HORIZONTAL_BLOCKS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// Draws how many Tasks were still open at the end of each day as a column
+/// chart.
+///
+/// # Errors
+///
+/// Returns an error if the range ends before it starts.
+pub fn print_burndown(
+    to_do: &ToDo,
+    config: &Configuration,
+    options: &BurndownReport,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let until = options.until.unwrap_or(today);
+    let since = options.since.unwrap_or_else(|| days_before(until, 13));
+    check_range(since, until, config)?;
+
+    let axis = 6;
+    let available = terminal_width().saturating_sub(axis + 2).max(1);
+
+    let mut days =
+        burndown(to_do, options.project.as_deref(), since, until, &Local);
+
+    if days.len() > available {
+        days.drain(..days.len() - available);
+    }
+
+    let title = config.text("open_tasks");
+
+    let mut output = String::new();
+    push_title(
+        &mut output,
+        title,
+        options.project.as_deref(),
+        &days,
+        config,
+    );
+
+    let max = days.iter().map(|(_, open)| *open).max().unwrap_or(0);
+
+    if max == 0 {
+        let _ = writeln!(output, "{}", config.text("no_open_tasks"));
+
+        print!("{output}");
+        return Ok(());
+    }
+
+    let column = (available / days.len()).clamp(1, 3);
+    let bar = if column == 1 { 1 } else { column - 1 };
+
+    for row in (0..CHART_HEIGHT).rev() {
+        let label = match row {
+            0 => "0".to_string(),
+            _ if row == CHART_HEIGHT - 1 => max.to_string(),
+            _ => String::new(),
+        };
+        let tick = if label.is_empty() { '│' } else { '┤' };
+
+        let _ = write!(output, "{label:>width$} {tick}", width = axis - 2);
+
+        for (_, open) in &days {
+            let eighths = open * CHART_HEIGHT * 8 / max;
+            let filled = eighths.saturating_sub(row * 8).min(8);
+
+            if filled == 0 {
+                output.push_str(&" ".repeat(bar));
+            } else {
+                let cell = VERTICAL_BLOCKS[filled - 1].to_string().repeat(bar);
+                let _ = write!(output, "{}", cell.style(config.theme.chart()));
+            }
+
+            output.push_str(&" ".repeat(column - bar));
+        }
+
+        output.push('\n');
+    }
+
+    let width = days.len() * column;
+    let _ = writeln!(output, "{} └{}", " ".repeat(axis - 2), "─".repeat(width));
+    push_date_axis(&mut output, axis, width, &days);
+
+    print!("{output}");
+
+    Ok(())
+}
+
+/// Draws how many Tasks were completed during each day or week as a bar
+/// chart.
+///
+/// # Errors
+///
+/// Returns an error if the range ends before it starts.
+pub fn print_activity(
+    to_do: &ToDo,
+    config: &Configuration,
+    options: &ActivityReport,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let until = options.until.unwrap_or(today);
+    let since = options.since.unwrap_or_else(|| match options.by {
+        ReportPeriod::Day => days_before(until, 13),
+        ReportPeriod::Week => days_before(until, 7 * 7),
+    });
+    check_range(since, until, config)?;
+
+    let periods = completions(to_do, options.by.into(), since, until, &Local);
+
+    let title = config.text(match options.by {
+        ReportPeriod::Day => "completed_per_day",
+        ReportPeriod::Week => "completed_per_week",
+    });
+
+    let mut output = String::new();
+    push_title(&mut output, title, None, &periods, config);
+
+    let max = periods.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let total: usize = periods.iter().map(|(_, count)| count).sum();
+    let count_width = max.to_string().len();
+
+    // Dates take 10 columns, plus the spaces around the bar and the count.
+    let available =
+        terminal_width().saturating_sub(10 + 3 + count_width).max(1);
+
+    for (start, count) in &periods {
+        let eighths = (count * available * 8).checked_div(max).unwrap_or(0);
+
+        let mut bar = HORIZONTAL_BLOCKS[7].to_string().repeat(eighths / 8);
+
+        if eighths % 8 > 0 {
+            bar.push(HORIZONTAL_BLOCKS[eighths % 8 - 1]);
+        }
+
+        let _ = writeln!(
+            output,
+            "{start} {} {count}",
+            bar.style(config.theme.success())
+        );
+    }
+
+    let _ = writeln!(
+        output,
+        "\n{}: {total}",
+        config.text("total").style(config.theme.emphasis())
+    );
+
+    print!("{output}");
+
+    Ok(())
+}
+
+/// Prints the Tasks matching a report from the configuration file as a table,
+/// narrowed down by any extra filter terms and the given context.
+///
+/// # Errors
+///
+/// Returns an error if there's no report with the given name, or if its
+/// filter, its sort order or the extra terms are invalid.
+pub fn print_custom_report(
+    to_do: &ToDo,
+    config: &Configuration,
+    name: &str,
+    terms: &[String],
+    context: &Filter,
+) -> anyhow::Result<()> {
+    let Some(report) = config.reports.get(name) else {
+        bail!("{}", config.format("unknown_report", &[("name", &name)]))
+    };
+
+    let filter = match report.filter.parse::<Filter>() {
+        Ok(filter) => filter.and(terms.join(" ").parse()?).and(context.clone()),
+        Err(err) => bail!(
+            "{}: {err}",
+            config.format("report_failed", &[("name", &name)])
+        ),
+    };
+
+    let sort_keys = match report
+        .sort
+        .iter()
+        .map(|key| key.parse::<SortKey>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(keys) => keys,
+        Err(err) => bail!(
+            "{}: {err}",
+            config.format("report_failed", &[("name", &name)])
+        ),
+    };
+
+    let today = Local::now().date_naive();
+    let mut tasks: Vec<Task> = to_do
+        .tasks
+        .iter()
+        .filter(|task| filter.matches(task, to_do, today))
+        .cloned()
+        .collect();
+
+    sort_tasks(&mut tasks, &sort_keys);
+
+    if let Some(limit) = report.limit {
+        tasks.truncate(limit);
+    }
+
+    if tasks.is_empty() {
+        println!("{}", config.text("no_tasks_match_report"));
+
+        return Ok(());
+    }
+
+    let mut output = String::new();
+
+    if report.group_by == GroupBy::None {
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        push_table(&mut output, &tasks, &report.columns, config);
+    } else {
+        for (group, tasks) in group_tasks(&tasks, report.group_by, config) {
+            let _ =
+                writeln!(output, "{}\n", group.style(config.theme.heading()));
+            push_table(&mut output, &tasks, &report.columns, config);
+            output.push('\n');
+        }
+    }
+
+    print!("{output}");
+
+    Ok(())
+}
+
+/// Splits Tasks into titled groups in order of appearance. Tasks with several
+/// tags appear once for each of them.
+fn group_tasks<'a>(
+    tasks: &'a [Task],
+    group_by: GroupBy,
+    config: &Configuration,
+) -> IndexMap<String, Vec<&'a Task>> {
+    let mut groups: IndexMap<String, Vec<&Task>> = IndexMap::new();
+
+    for task in tasks {
+        let titles = match group_by {
+            GroupBy::None => vec![String::new()],
+            GroupBy::Project => {
+                vec![task.project.style(config.theme.project()).to_string()]
+            }
+            GroupBy::State => vec![state_name(task.state, config)],
+            GroupBy::Tag if task.tags.is_empty() => {
+                let untagged = config.text("untagged");
+
+                vec![untagged.style(config.theme.muted()).to_string()]
+            }
+            GroupBy::Tag => task
+                .tags
+                .iter()
+                .map(|tag| {
+                    format!("#{tag}").style(config.theme.tag()).to_string()
+                })
+                .collect(),
+        };
+
+        for title in titles {
+            groups.entry(title).or_default().push(task);
+        }
+    }
+
+    groups
+}
+
+fn days_before(date: NaiveDate, days: u64) -> NaiveDate {
+    date.checked_sub_days(Days::new(days)).unwrap_or(date)
+}
+
+fn check_range(
+    since: NaiveDate,
+    until: NaiveDate,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    if since > until {
+        bail!(
+            "{}",
+            config.format(
+                "invalid_range",
+                &[("since", &since), ("until", &until)]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+fn push_title(
+    output: &mut String,
+    title: &str,
+    project: Option<&str>,
+    points: &[(NaiveDate, usize)],
+    config: &Configuration,
+) {
+    let _ = write!(output, "{}", title.style(config.theme.heading()));
+
+    if let Some(project) = project {
+        let _ = write!(output, " {}", project.style(config.theme.project()));
+    }
+
+    if let (Some((first, _)), Some((last, _))) = (points.first(), points.last())
+    {
+        let _ = write!(output, " ({first} - {last})");
+    }
+
+    output.push_str("\n\n");
+}
+
+/// Pushes the first and last dates of a chart below its columns.
+fn push_date_axis(
+    output: &mut String,
+    axis: usize,
+    width: usize,
+    days: &[(NaiveDate, usize)],
+) {
+    let (Some((first, _)), Some((last, _))) = (days.first(), days.last())
+    else {
+        return;
+    };
+
+    let first = first.format("%m-%d").to_string();
+    let last = last.format("%m-%d").to_string();
+
+    let _ = write!(output, "{}{first}", " ".repeat(axis));
+
+    if days.len() > 1 {
+        let gap = width.saturating_sub(first.len() + last.len()).max(1);
+        let _ = write!(output, "{}{last}", " ".repeat(gap));
+    }
+
+    output.push('\n');
+}
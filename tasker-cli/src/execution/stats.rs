@@ -0,0 +1,149 @@
+use super::helpers::{plain_width, state_name};
+use crate::config::Configuration;
+use anstream::print;
+use lib_tasker::{
+    statistics::{GroupCount, Statistics},
+    todos::State,
+};
+use owo_colors::OwoColorize;
+use std::fmt::Write;
+
+/// Prints the given statistics in the configured language.
+pub fn print_stats(stats: &Statistics, config: &Configuration) {
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        "{}\n",
+        config.text("statistics").style(config.theme.heading())
+    );
+    let _ = writeln!(
+        output,
+        "{}: {}  ({}: {:.0}%)\n",
+        config.text("tasks"),
+        stats.total.style(config.theme.emphasis()),
+        config.text("stats_completed"),
+        stats.completion_ratio * 100.0
+    );
+
+    push_rows(
+        &mut output,
+        config,
+        config.text("by_state"),
+        [
+            (State::ToDo, stats.states.to_do),
+            (State::Doing, stats.states.doing),
+            (State::Waiting, stats.states.waiting),
+            (State::Done, stats.states.done),
+        ]
+        .into_iter()
+        .map(|(state, count)| (state_name(state, config), count.to_string()))
+        .collect(),
+    );
+
+    let done = config.text("stats_done");
+
+    push_rows(
+        &mut output,
+        config,
+        config.text("by_project"),
+        group_rows(&stats.projects, "", done),
+    );
+    push_rows(
+        &mut output,
+        config,
+        config.text("by_tag"),
+        group_rows(&stats.tags, "#", done),
+    );
+
+    push_rows(
+        &mut output,
+        config,
+        config.text("weekly_activity"),
+        stats
+            .weeks
+            .iter()
+            .map(|week| {
+                (
+                    format!("{} {}", config.text("week_of"), week.week),
+                    format!(
+                        "+{} {}, {} {}",
+                        week.added,
+                        config.text("stats_added"),
+                        week.completed,
+                        config.text("stats_completed")
+                    ),
+                )
+            })
+            .collect(),
+    );
+
+    push_rows(
+        &mut output,
+        config,
+        config.text("oldest_open_tasks"),
+        stats
+            .oldest_open
+            .iter()
+            .map(|task| {
+                (
+                    format!("{}. {}", task.id, task.description),
+                    format!(
+                        "[{}] {}",
+                        state_name(task.state, config),
+                        task.created_at.map_or_else(
+                            || config.text("unknown_date").to_string(),
+                            |created| created.date_naive().to_string()
+                        )
+                    ),
+                )
+            })
+            .collect(),
+    );
+
+    print!("{output}");
+}
+
+fn group_rows(
+    groups: &[GroupCount],
+    prefix: &str,
+    done: &str,
+) -> Vec<(String, String)> {
+    groups
+        .iter()
+        .map(|group| {
+            (
+                format!("{prefix}{}", group.name),
+                format!("{} ({} {done})", group.total, group.done),
+            )
+        })
+        .collect()
+}
+
+/// Pushes a titled section with its rows aligned in two columns, unless it
+/// has no rows.
+fn push_rows(
+    output: &mut String,
+    config: &Configuration,
+    title: &str,
+    rows: Vec<(String, String)>,
+) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let width = rows
+        .iter()
+        .map(|(name, _)| plain_width(name))
+        .max()
+        .unwrap_or(0);
+
+    let _ = writeln!(output, "{}", title.style(config.theme.heading()));
+
+    for (name, value) in rows {
+        let padding = " ".repeat(width - plain_width(&name));
+        let _ = writeln!(output, "  {name}{padding}  {value}");
+    }
+
+    output.push('\n');
+}
@@ -0,0 +1,218 @@
+use super::helpers::{plain_width, state_name, terminal_width, truncate};
+use crate::config::{Column, Configuration};
+use chrono::Local;
+use itertools::Itertools;
+use lib_tasker::todos::Task;
+use owo_colors::OwoColorize;
+use std::fmt::Write;
+
+/// Narrowest the description column gets before letting the table overflow.
+const MIN_DESCRIPTION_WIDTH: usize = 12;
+
+/// Space left between columns.
+const GAP: &str = "  ";
+
+/// Pushes the given Tasks as a table with one row each, shortening their
+/// descriptions to fit the width of the terminal.
+pub fn push_table(
+    output: &mut String,
+    tasks: &[&Task],
+    columns: &[Column],
+    config: &Configuration,
+) {
+    push_table_within(output, tasks, columns, config, terminal_width());
+}
+
+/// Pushes the given Tasks as a table with one row each, shortening their
+/// descriptions so each row fits within `width` columns.
+fn push_table_within(
+    output: &mut String,
+    tasks: &[&Task],
+    columns: &[Column],
+    config: &Configuration,
+    width: usize,
+) {
+    let headers: Vec<String> = columns
+        .iter()
+        .map(|&column| column_title(column, config).to_string())
+        .collect();
+
+    let mut rows: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|task| {
+            columns
+                .iter()
+                .map(|&column| cell(task, column, config))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> =
+        headers.iter().map(|title| plain_width(title)).collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(plain_width(cell));
+        }
+    }
+
+    if let Some(index) = columns
+        .iter()
+        .position(|&column| column == Column::Description)
+    {
+        // Every column but the last is followed by a gap, the description's
+        // own included.
+        let others: usize = widths
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| position != index)
+            .map(|(_, width)| width)
+            .sum::<usize>()
+            + GAP.len() * (columns.len() - 1);
+
+        let available = width.saturating_sub(others).max(MIN_DESCRIPTION_WIDTH);
+
+        if widths[index] > available {
+            widths[index] = available;
+
+            for row in &mut rows {
+                row[index] = truncate(&row[index], available);
+            }
+        }
+    }
+
+    push_row(output, &headers, &widths, |title| {
+        title.style(config.theme.heading()).to_string()
+    });
+
+    for row in &rows {
+        push_row(output, row, &widths, ToString::to_string);
+    }
+}
+
+/// Pushes a row of cells padded to the given widths.
+fn push_row(
+    output: &mut String,
+    cells: &[String],
+    widths: &[usize],
+    style: impl Fn(&str) -> String,
+) {
+    let mut line = String::new();
+
+    for (cell, width) in cells.iter().zip(widths) {
+        let padding = width.saturating_sub(plain_width(cell));
+        let _ = write!(line, "{}{}{GAP}", style(cell), " ".repeat(padding));
+    }
+
+    let _ = writeln!(output, "{}", line.trim_end());
+}
+
+fn cell(task: &Task, column: Column, config: &Configuration) -> String {
+    match column {
+        Column::Id => task.id.style(config.theme.id()).to_string(),
+        Column::State => state_name(task.state, config),
+        Column::Description => task.description.clone(),
+        Column::Project => task.project.clone(),
+        Column::Tags => task.tags.iter().join(", "),
+        Column::Due => task.due.map(|due| due.to_string()).unwrap_or_default(),
+        Column::Estimate => task
+            .estimate
+            .map(|estimate| estimate.to_string())
+            .unwrap_or_default(),
+        Column::Created => task
+            .created_at
+            .map(|created| {
+                created.with_timezone(&Local).date_naive().to_string()
+            })
+            .unwrap_or_default(),
+        Column::Completed => task
+            .completed_at
+            .map(|completed| {
+                completed.with_timezone(&Local).date_naive().to_string()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn column_title(column: Column, config: &Configuration) -> &'static str {
+    config.text(match column {
+        Column::Id => "id",
+        Column::State => "state",
+        Column::Description => "description",
+        Column::Project => "project",
+        Column::Tags => "tags",
+        Column::Due => "due",
+        Column::Estimate => "estimate",
+        Column::Created => "created",
+        Column::Completed => "completed",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use anstream::adapter::strip_str;
+
+    fn table(tasks: &[Task], columns: &[Column], width: usize) -> String {
+        let config = Configuration::fallback(Language::English);
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        let mut output = String::new();
+
+        push_table_within(&mut output, &tasks, columns, &config, width);
+
+        output
+    }
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task::create("Write the documentation of every command")
+                .id(1)
+                .project("Docs")
+                .build(),
+            Task::create("Fix it").id(12).project("Inbox").build(),
+        ]
+    }
+
+    #[test]
+    fn aligns_columns() {
+        let output = table(
+            &tasks(),
+            &[Column::Id, Column::Project, Column::Description],
+            80,
+        );
+        let output = strip_str(&output).to_string();
+        let starts: Vec<Option<usize>> = output
+            .lines()
+            .zip(["Description", "Write", "Fix"])
+            .map(|(line, start)| line.find(start))
+            .collect();
+
+        assert_eq!(starts, [Some(13), Some(13), Some(13)]);
+    }
+
+    #[test]
+    fn fits_descriptions_anywhere_within_the_width() {
+        for columns in [
+            [Column::Id, Column::Project, Column::Description],
+            [Column::Id, Column::Description, Column::Project],
+            [Column::Description, Column::Id, Column::Project],
+        ] {
+            let output = table(&tasks(), &columns, 30);
+
+            for line in output.lines() {
+                assert!(plain_width(line) <= 30, "{line:?} overflows");
+            }
+
+            assert!(output.contains('…'));
+        }
+    }
+
+    #[test]
+    fn keeps_descriptions_that_fit() {
+        let output = table(&tasks(), &[Column::Id, Column::Description], 80);
+
+        assert!(output.contains("Write the documentation of every command"));
+        assert!(!output.contains('…'));
+    }
+}
@@ -0,0 +1,112 @@
+use super::helpers::{format_duration, start_of_day};
+use crate::config::Configuration;
+use anstream::print;
+use chrono::{Days, Duration, NaiveDate, Utc};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use lib_tasker::todos::ToDo;
+use owo_colors::OwoColorize;
+use std::fmt::Write;
+use unicode_width::UnicodeWidthStr;
+
+/// Prints the time spent on each Task, project and tag between the given
+/// days, both included.
+pub fn print_timesheet(
+    to_do: &ToDo,
+    config: &Configuration,
+    since: NaiveDate,
+    until: NaiveDate,
+) {
+    let from = start_of_day(since);
+    let until =
+        start_of_day(until.checked_add_days(Days::new(1)).unwrap_or(until));
+    let now = Utc::now();
+
+    let mut tasks = Vec::new();
+    let mut projects: IndexMap<String, Duration> = IndexMap::new();
+    let mut tags: IndexMap<String, Duration> = IndexMap::new();
+
+    for task in &to_do.tasks {
+        let time = task.tracked_time(from, until, now);
+
+        if time <= Duration::zero() {
+            continue;
+        }
+
+        tasks.push((format!("{}. {}", task.id, task.description), time));
+        add_time(&mut projects, &task.project, time);
+
+        for tag in &task.tags {
+            add_time(&mut tags, &format!("#{tag}"), time);
+        }
+    }
+
+    let total = tasks
+        .iter()
+        .fold(Duration::zero(), |total, (_, time)| total + *time);
+
+    let labels = ["timesheet", "tasks", "projects", "tags", "total"]
+        .map(|key| config.text(key));
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "{} ({since} - {})\n",
+        labels[0].style(config.theme.heading()),
+        until.date_naive().pred_opt().unwrap_or(since)
+    );
+
+    push_section(&mut output, config, labels[1], tasks);
+    push_section(&mut output, config, labels[2], sorted(projects));
+    push_section(&mut output, config, labels[3], sorted(tags));
+
+    let _ = writeln!(
+        output,
+        "{}: {}",
+        labels[4].style(config.theme.emphasis()),
+        format_duration(total).style(config.theme.success().bold())
+    );
+
+    print!("{output}");
+}
+
+fn add_time(times: &mut IndexMap<String, Duration>, key: &str, time: Duration) {
+    let entry = times.entry(key.to_string()).or_insert_with(Duration::zero);
+    *entry += time;
+}
+
+fn sorted(times: IndexMap<String, Duration>) -> Vec<(String, Duration)> {
+    times
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.to_lowercase().cmp(&b.to_lowercase()))
+        .collect()
+}
+
+fn push_section(
+    output: &mut String,
+    config: &Configuration,
+    title: &str,
+    rows: Vec<(String, Duration)>,
+) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let width = rows.iter().map(|(name, _)| name.width()).max().unwrap_or(0);
+
+    let _ = writeln!(output, "{}", title.style(config.theme.heading()));
+
+    for (name, time) in rows {
+        let padding = " ".repeat(width - name.width());
+        let _ =
+            writeln!(output, "  {name}{padding}  {}", format_duration(time));
+    }
+
+    output.push('\n');
+}
+
+/// Returns the default first day of a timesheet, a week before today.
+#[must_use]
+pub fn default_since(today: NaiveDate) -> NaiveDate {
+    today.checked_sub_days(Days::new(6)).unwrap_or(today)
+}
@@ -0,0 +1,194 @@
+use super::{
+    config::{describe, open_document, save_document},
+    helpers::{filter_tasks, plain_width, push_compact_task},
+};
+use crate::{
+    cli::{ListTasks, SwitchWorkspace},
+    config::{Configuration, SettingError, DEFAULT_WORKSPACE},
+};
+use anstream::{print, println};
+use anyhow::bail;
+use camino::Utf8PathBuf;
+use itertools::Itertools;
+use lib_tasker::todos::ToDo;
+use owo_colors::OwoColorize;
+use std::fmt::Write;
+
+/// Prints every workspace with its Tasks file, marking the one in use.
+pub fn list_workspaces(config: &Configuration) {
+    let workspaces = config.workspace_paths();
+    let width = workspaces
+        .iter()
+        .map(|(name, _)| plain_width(name))
+        .max()
+        .unwrap_or_default();
+
+    for (name, path) in workspaces {
+        let marker = if name == config.active_workspace() {
+            "*"
+        } else {
+            " "
+        };
+        let padding = " ".repeat(width - plain_width(name));
+
+        println!(
+            "{} {}{padding}  {path}",
+            marker.style(config.theme.success()),
+            name.style(config.theme.label())
+        );
+    }
+}
+
+/// Selects the workspace to use from now on in the configuration file at the
+/// given path.
+///
+/// # Errors
+///
+/// Returns an error if the workspace doesn't exist or if the configuration
+/// file couldn't be read or written.
+pub fn switch_workspace(
+    switch: &SwitchWorkspace,
+    path: &Utf8PathBuf,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let name = switch.name.as_str();
+
+    let Some(to_do_path) = config.workspace_path(name) else {
+        bail!("{}", unknown_workspace(name, config));
+    };
+
+    let mut document = open_document(path, config)?;
+
+    let result = if name == DEFAULT_WORKSPACE {
+        document.unset("workspace")
+    } else {
+        document.set("workspace", name)
+    };
+
+    match result {
+        Ok(_) | Err(SettingError::NotSet(_)) => {}
+        Err(err) => return Err(describe(err, path, config)),
+    }
+
+    save_document(&document, path, config)?;
+
+    println!(
+        "{}: {to_do_path}",
+        config
+            .format("switched_workspace", &[("name", &name)])
+            .style(config.theme.success())
+    );
+
+    Ok(())
+}
+
+/// Describes a workspace that isn't in the configuration, listing the ones
+/// that are.
+pub fn unknown_workspace(name: &str, config: &Configuration) -> String {
+    let available = config
+        .workspace_paths()
+        .iter()
+        .map(|(name, _)| format!("`{name}`"))
+        .join(", ");
+
+    config.format(
+        "workspace_unknown",
+        &[("name", &name), ("available", &available)],
+    )
+}
+
+/// Prints the Tasks of every workspace matching the given filters, a line
+/// each, with their IDs prefixed by the name of their workspace.
+///
+/// # Errors
+///
+/// Returns an error if the Tasks file of any workspace can't be read.
+pub fn list_all_workspaces(
+    options: &ListTasks,
+    config: &Configuration,
+) -> anyhow::Result<()> {
+    let mut output = String::new();
+
+    for (name, path) in config.workspace_paths() {
+        let to_do = ToDo::get_to_do(path)?;
+
+        for task in filter_tasks(&to_do, options) {
+            let _ = write!(output, "{}:", name.style(config.theme.label()));
+            push_compact_task(&task, &mut output, config, 0, true);
+        }
+    }
+
+    print!("{output}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Language, Workspace};
+
+    fn config() -> Configuration {
+        let mut config = Configuration::fallback(Language::English);
+        config.workspaces.insert(
+            "work".to_string(),
+            Workspace {
+                to_do_path: Utf8PathBuf::from("/work.ron"),
+                language: None,
+                theme: None,
+            },
+        );
+
+        config
+    }
+
+    fn switch(name: &str, path: &Utf8PathBuf) -> anyhow::Result<String> {
+        switch_workspace(
+            &SwitchWorkspace {
+                name: name.to_string(),
+            },
+            path,
+            &config(),
+        )?;
+
+        Ok(std::fs::read_to_string(path)?)
+    }
+
+    #[test]
+    fn switches_to_workspaces_by_name() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path =
+            Utf8PathBuf::try_from(directory.path().join("tasker-cli.toml"))
+                .expect("temporary paths should be UTF-8");
+        let contents = "name = \"A\"\n\n[workspaces.work]\n\
+                        to_do_path = \"/work.ron\"\n";
+
+        std::fs::write(&path, contents).expect("file should be written");
+
+        assert_eq!(
+            switch(DEFAULT_WORKSPACE, &path).expect("nothing to unset"),
+            contents
+        );
+        assert_eq!(
+            switch("work", &path).expect("the workspace exists"),
+            "name = \"A\"\nworkspace = \"work\"\n\n[workspaces.work]\n\
+             to_do_path = \"/work.ron\"\n"
+        );
+        assert_eq!(
+            switch(DEFAULT_WORKSPACE, &path).expect("the workspace is set"),
+            contents
+        );
+    }
+
+    #[test]
+    fn lists_the_workspaces_that_exist_when_one_doesnt() {
+        let path = Utf8PathBuf::from("/nonexistent/tasker-cli.toml");
+        let err = switch("Work", &path).expect_err("names are exact");
+
+        assert_eq!(err.to_string(), unknown_workspace("Work", &config()));
+        assert_eq!(
+            unknown_workspace("Work", &config()),
+            "There's no workspace named `Work`. Available: `default`, `work`"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::config::Language;
+use serde::Deserialize;
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+/// Messages of a single language, keyed by their names in the catalogue
+/// files under `locales/`.
+#[derive(Debug)]
+pub struct Catalogue {
+    language: Language,
+    messages: HashMap<String, Message>,
+    help: HashMap<String, String>,
+    values: HashMap<String, ValueText>,
+}
+
+/// Contents of a catalogue file. Help texts of commands and arguments live
+/// under `[help]`, and those of the values they accept under `[values]`.
+#[derive(Debug, Deserialize)]
+struct CatalogueFile {
+    #[serde(default)]
+    help: HashMap<String, String>,
+    #[serde(default)]
+    values: HashMap<String, ValueText>,
+    #[serde(flatten)]
+    messages: HashMap<String, Message>,
+}
+
+/// Help text of a value accepted by an argument, and other names it can be
+/// written as in this language.
+#[derive(Debug, Deserialize)]
+pub struct ValueText {
+    pub help: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// A message either reads the same for every amount or has a form for each
+/// plural category of its language.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Text(String),
+    Plural { one: String, other: String },
+}
+
+/// The placeholders of a message, written as `{name}` within it, and what to
+/// replace them with.
+pub type Arguments<'a> = [(&'a str, &'a dyn Display)];
+
+impl Catalogue {
+    /// Returns the message with the given key, falling back to English if
+    /// this catalogue lacks it and to the key itself if every catalogue does.
+    #[must_use]
+    pub fn text(&'static self, key: &'static str) -> &'static str {
+        match self.messages.get(key) {
+            Some(Message::Text(text)) => text,
+            Some(Message::Plural { other, .. }) => other,
+            None if !std::ptr::eq(self, catalogue(Language::English)) => {
+                catalogue(Language::English).text(key)
+            }
+            None => key,
+        }
+    }
+
+    /// Returns the message with the given key with its placeholders filled in.
+    #[must_use]
+    pub fn format(
+        &'static self,
+        key: &'static str,
+        arguments: &Arguments,
+    ) -> String {
+        fill(self.text(key), arguments)
+    }
+
+    /// Returns the form of the message with the given key matching `count`
+    /// in this language, with its placeholders filled in. The `{count}`
+    /// placeholder is always available.
+    #[must_use]
+    pub fn plural(
+        &'static self,
+        key: &'static str,
+        count: usize,
+        arguments: &Arguments,
+    ) -> String {
+        let text = match self.messages.get(key) {
+            Some(Message::Plural { one, other }) => {
+                if is_singular(self.language, count) {
+                    one
+                } else {
+                    other
+                }
+            }
+            _ => self.text(key),
+        };
+
+        fill(&fill(text, arguments), &[("count", &count)])
+    }
+
+    /// Returns the help text of a command or argument, given as the names of
+    /// the subcommands leading to it and its own, joined by dots.
+    #[must_use]
+    pub fn help(&'static self, key: &str) -> Option<&'static str> {
+        self.help.get(key).map(String::as_str)
+    }
+
+    /// Returns the help text and aliases of a value, given as the name of the
+    /// kind of value and its own, joined by a dot.
+    #[must_use]
+    pub fn value(&'static self, key: &str) -> Option<&'static ValueText> {
+        self.values.get(key)
+    }
+}
+
+/// Whether `count` takes the singular form in the given language. Every
+/// supported language only tells apart one thing from many, though French
+/// and Portuguese count zero as one.
+const fn is_singular(language: Language, count: usize) -> bool {
+    match language {
+        Language::French | Language::Portuguese => count <= 1,
+        Language::English | Language::Spanish | Language::German => count == 1,
+    }
+}
+
+fn fill(text: &str, arguments: &Arguments) -> String {
+    arguments
+        .iter()
+        .fold(text.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// Returns the catalogue of the given language, parsing it the first time
+/// it's needed.
+///
+/// # Panics
+///
+/// Panics if the bundled catalogue isn't valid TOML.
+#[must_use]
+pub fn catalogue(language: Language) -> &'static Catalogue {
+    static CATALOGUES: [OnceLock<Catalogue>; 5] = [
+        OnceLock::new(),
+        OnceLock::new(),
+        OnceLock::new(),
+        OnceLock::new(),
+        OnceLock::new(),
+    ];
+
+    let (index, source) = match language {
+        Language::English => (0, include_str!("../../locales/en.toml")),
+        Language::Spanish => (1, include_str!("../../locales/es.toml")),
+        Language::Portuguese => (2, include_str!("../../locales/pt.toml")),
+        Language::French => (3, include_str!("../../locales/fr.toml")),
+        Language::German => (4, include_str!("../../locales/de.toml")),
+    };
+
+    CATALOGUES[index].get_or_init(|| {
+        let file: CatalogueFile =
+            toml::from_str(source).expect("bundled catalogues should be valid");
+
+        Catalogue {
+            language,
+            messages: file.messages,
+            help: file.help,
+            values: file.values,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cli::{
+            Cli, ColorWhen, ReportPeriod, SortTasks, ToggleState,
+            TranslatedValue,
+        },
+        config::ListFormat,
+    };
+    use clap::{Command, CommandFactory, ValueEnum};
+
+    #[test]
+    fn fills_every_placeholder() {
+        assert_eq!(
+            fill(
+                "{done}/{total} done, {done} so far, {missing}",
+                &[("done", &3), ("total", &"5")]
+            ),
+            "3/5 done, 3 so far, {missing}"
+        );
+    }
+
+    #[test]
+    fn chooses_the_plural_form_of_each_language() {
+        assert!(!is_singular(Language::English, 0));
+        assert!(is_singular(Language::English, 1));
+        assert!(!is_singular(Language::German, 2));
+        assert!(is_singular(Language::French, 0));
+        assert!(is_singular(Language::Portuguese, 1));
+        assert!(!is_singular(Language::Spanish, 0));
+    }
+
+    #[test]
+    fn formats_plural_messages() {
+        let english = catalogue(Language::English);
+
+        assert_eq!(english.plural("note_count", 1, &[]), "+1 note");
+        assert_eq!(english.plural("note_count", 0, &[]), "+0 notes");
+        assert_eq!(
+            english.plural("subtasks_done", 2, &[("done", &1), ("total", &2)]),
+            "1/2 subtasks done"
+        );
+        assert_eq!(
+            catalogue(Language::French).plural("note_count", 0, &[]),
+            "+0 note"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        assert_eq!(
+            catalogue(Language::German).text("not_a_message"),
+            "not_a_message"
+        );
+        assert_eq!(
+            catalogue(Language::English).help("add"),
+            None,
+            "English help texts come from the documentation"
+        );
+    }
+
+    #[test]
+    fn every_catalogue_has_the_english_messages() {
+        let english = catalogue(Language::English);
+
+        for language in [
+            Language::Spanish,
+            Language::Portuguese,
+            Language::French,
+            Language::German,
+        ] {
+            let missing: Vec<_> = english
+                .messages
+                .keys()
+                .filter(|key| !catalogue(language).messages.contains_key(*key))
+                .collect();
+
+            assert!(missing.is_empty(), "{language:?} lacks {missing:?}");
+
+            let help = help_keys(&Cli::command(), "");
+            let unknown: Vec<_> = catalogue(language)
+                .help
+                .keys()
+                .filter(|key| *key != "tasker" && !help.contains(key))
+                .collect();
+
+            assert!(unknown.is_empty(), "{language:?} helps {unknown:?}");
+
+            let values = [
+                value_keys::<ColorWhen>(),
+                value_keys::<ListFormat>(),
+                value_keys::<ReportPeriod>(),
+                value_keys::<SortTasks>(),
+                value_keys::<ToggleState>(),
+            ]
+            .concat();
+            let unknown: Vec<_> = catalogue(language)
+                .values
+                .keys()
+                .filter(|key| !values.contains(key))
+                .collect();
+
+            assert!(unknown.is_empty(), "{language:?} names {unknown:?}");
+        }
+    }
+
+    /// Returns the keys of the help texts of a command, its arguments and
+    /// its subcommands, including the `help` ones clap adds.
+    fn help_keys(command: &Command, path: &str) -> Vec<String> {
+        let key = |name: &str| {
+            if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{path}.{name}")
+            }
+        };
+
+        let mut keys: Vec<String> = command
+            .get_arguments()
+            .map(|arg| key(arg.get_id().as_str()))
+            .collect();
+
+        for subcommand in command.get_subcommands() {
+            let path = key(subcommand.get_name());
+            keys.extend(help_keys(subcommand, &path));
+            keys.push(path);
+        }
+
+        keys.push("help".to_string());
+        keys
+    }
+
+    fn value_keys<E: TranslatedValue>() -> Vec<String> {
+        E::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| format!("{}.{}", E::KEY, value.get_name()))
+            .collect()
+    }
+}
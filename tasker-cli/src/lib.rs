@@ -1,3 +1,5 @@
 pub mod cli;
 pub mod config;
 pub mod execution;
+pub mod i18n;
+pub mod tui;
@@ -1,10 +1,15 @@
-use clap::Parser;
+use std::process::ExitCode;
 use tasker_cli::{cli::Cli, execution::execute_application};
 
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let cli = Cli::parse_localized();
 
-    execute_application(cli)?;
+    // Errors may carry colours, so they go through the same stream as the
+    // rest of the output to have them removed when disabled.
+    if let Err(err) = execute_application(cli) {
+        anstream::eprintln!("Error: {err:?}");
+        return ExitCode::FAILURE;
+    }
 
-    Ok(())
+    ExitCode::SUCCESS
 }
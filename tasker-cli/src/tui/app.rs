@@ -0,0 +1,602 @@
+use crate::config::Configuration;
+use chrono::{Local, Utc};
+use itertools::Itertools;
+use lib_tasker::todos::{State, Task, ToDo};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Projects,
+    Tags,
+    Tasks,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Filter,
+    Add,
+    Edit(usize),
+}
+
+#[derive(Debug)]
+pub struct App<'a> {
+    pub config: &'a Configuration,
+    pub to_do: ToDo,
+    pub pane: Pane,
+    pub mode: Mode,
+    pub input: String,
+    pub filter: String,
+    /// Selected project in the sidebar, where 0 means every project.
+    pub project: usize,
+    /// Selected tag in the sidebar, where 0 means every tag.
+    pub tag: usize,
+    /// Selected Task within the visible ones.
+    pub task: usize,
+    pub message: Option<String>,
+    /// Why the last action failed, shown until the next key press.
+    pub error: Option<String>,
+    pub should_quit: bool,
+    modified: Option<SystemTime>,
+}
+
+impl<'a> App<'a> {
+    /// Loads the Task file set in the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it failed to read the Task file.
+    pub fn new(config: &'a Configuration) -> anyhow::Result<Self> {
+        Ok(Self {
+            config,
+            to_do: ToDo::get_to_do(&config.to_do_path)?,
+            pane: Pane::Tasks,
+            mode: Mode::Normal,
+            input: String::new(),
+            filter: String::new(),
+            project: 0,
+            tag: 0,
+            task: 0,
+            message: None,
+            error: None,
+            should_quit: false,
+            modified: modified_time(config),
+        })
+    }
+
+    #[must_use]
+    pub fn projects(&self) -> Vec<String> {
+        self.to_do
+            .tasks
+            .iter()
+            .map(|task| task.project.clone())
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    #[must_use]
+    pub fn tags(&self) -> Vec<String> {
+        self.to_do
+            .tasks
+            .iter()
+            .flat_map(|task| task.tags.iter().cloned())
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    fn selected_project(&self) -> Option<String> {
+        self.project
+            .checked_sub(1)
+            .and_then(|index| self.projects().get(index).cloned())
+    }
+
+    fn selected_tag(&self) -> Option<String> {
+        self.tag
+            .checked_sub(1)
+            .and_then(|index| self.tags().get(index).cloned())
+    }
+
+    /// Returns the Tasks matching the selected project, tag and filter.
+    #[must_use]
+    pub fn visible_tasks(&self) -> Vec<&Task> {
+        let project = self.selected_project();
+        let tag = self.selected_tag();
+        let filter = self.filter.to_lowercase();
+
+        self.to_do
+            .tasks
+            .iter()
+            .filter(|task| project.as_ref().is_none_or(|p| &task.project == p))
+            .filter(|task| tag.as_ref().is_none_or(|t| task.tags.contains(t)))
+            .filter(|task| task.description.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn selected_task_id(&self) -> Option<usize> {
+        self.visible_tasks().get(self.task).map(|task| task.id)
+    }
+
+    /// Reacts to a key press according to the current mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it failed to save the Task file after a change.
+    pub fn handle_key(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && key.code == KeyCode::Char('c')
+        {
+            self.should_quit = true;
+            return Ok(());
+        }
+
+        match self.mode {
+            Mode::Normal => self.handle_normal_key(key.code)?,
+            Mode::Filter => self.handle_filter_key(key.code),
+            Mode::Add | Mode::Edit(_) => self.handle_dialog_key(key.code)?,
+        }
+
+        Ok(())
+    }
+
+    fn handle_normal_key(&mut self, code: KeyCode) -> anyhow::Result<()> {
+        self.message = None;
+        self.error = None;
+
+        match code {
+            KeyCode::Esc if !self.filter.is_empty() => self.filter.clear(),
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => {
+                self.pane = match self.pane {
+                    Pane::Projects => Pane::Tags,
+                    Pane::Tags => Pane::Tasks,
+                    Pane::Tasks => Pane::Projects,
+                };
+            }
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => {
+                self.pane = match self.pane {
+                    Pane::Projects => Pane::Tasks,
+                    Pane::Tags => Pane::Projects,
+                    Pane::Tasks => Pane::Tags,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Char('1') => self.set_state(State::ToDo)?,
+            KeyCode::Char('2') => self.set_state(State::Doing)?,
+            KeyCode::Char('3') => self.set_state(State::Waiting)?,
+            KeyCode::Char('4') => self.set_state(State::Done)?,
+            KeyCode::Char(' ') => {
+                let done = self
+                    .selected_task_id()
+                    .and_then(|id| self.to_do.get_task(id))
+                    .is_some_and(|task| task.state == State::Done);
+
+                self.set_state(if done { State::ToDo } else { State::Done })?;
+            }
+            KeyCode::Char('/') => {
+                self.input.clone_from(&self.filter);
+                self.mode = Mode::Filter;
+            }
+            KeyCode::Char('a') => {
+                self.input.clear();
+                self.mode = Mode::Add;
+            }
+            KeyCode::Char('e') => {
+                if let Some(task) = self
+                    .selected_task_id()
+                    .and_then(|id| self.to_do.get_task(id))
+                {
+                    self.input.clone_from(&task.description);
+                    self.mode = Mode::Edit(task.id);
+                }
+            }
+            KeyCode::Char('r') => self.reload()?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_filter_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => self.mode = Mode::Normal,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.filter.clone_from(&self.input);
+            }
+            KeyCode::Char(character) => {
+                self.input.push(character);
+                self.filter.clone_from(&self.input);
+            }
+            _ => {}
+        }
+
+        self.task = 0;
+    }
+
+    fn handle_dialog_key(&mut self, code: KeyCode) -> anyhow::Result<()> {
+        match code {
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(character) => self.input.push(character),
+            KeyCode::Enter => {
+                let description = self.input.trim().to_string();
+
+                if !description.is_empty() {
+                    match self.mode {
+                        Mode::Add => self.add_task(description)?,
+                        Mode::Edit(id) => self.edit_task(id, description)?,
+                        Mode::Normal | Mode::Filter => {}
+                    }
+                }
+
+                self.mode = Mode::Normal;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = match self.pane {
+            Pane::Projects => self.projects().len() + 1,
+            Pane::Tags => self.tags().len() + 1,
+            Pane::Tasks => self.visible_tasks().len(),
+        };
+
+        let selected = match self.pane {
+            Pane::Projects => &mut self.project,
+            Pane::Tags => &mut self.tag,
+            Pane::Tasks => &mut self.task,
+        };
+
+        if len == 0 {
+            return;
+        }
+
+        *selected = selected.saturating_add_signed(delta).min(len - 1);
+
+        if self.pane != Pane::Tasks {
+            self.task = 0;
+        }
+    }
+
+    fn set_state(&mut self, state: State) -> anyhow::Result<()> {
+        let Some(id) = self.selected_task_id() else {
+            return Ok(());
+        };
+
+        let now = Local::now();
+        let transition = self.to_do.transition(
+            &[id],
+            state,
+            now.date_naive(),
+            now.with_timezone(&Utc),
+        )?;
+
+        self.save()?;
+
+        if !transition.recurrences.is_empty() {
+            self.message =
+                Some(self.config.text("next_occurrence_added").to_string());
+        }
+
+        Ok(())
+    }
+
+    fn add_task(&mut self, description: String) -> anyhow::Result<()> {
+        let mut task = Task::create(description);
+        task.id(self.to_do.next_id()).created_at(Utc::now());
+
+        if let Some(project) = self.selected_project() {
+            task.project(project);
+        }
+
+        if let Some(tag) = self.selected_tag() {
+            task.tag(tag);
+        }
+
+        self.to_do.add_task(task.build());
+        self.save()?;
+
+        self.message = Some(self.config.text("added_task").to_string());
+
+        Ok(())
+    }
+
+    fn edit_task(
+        &mut self,
+        id: usize,
+        description: String,
+    ) -> anyhow::Result<()> {
+        if let Some(task) =
+            self.to_do.tasks.iter_mut().find(|task| task.id == id)
+        {
+            task.change_description(description);
+        }
+
+        self.save()?;
+
+        self.message = Some(self.config.text("edited_task").to_string());
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> anyhow::Result<()> {
+        self.to_do.save(&self.config.to_do_path)?;
+        self.modified = modified_time(self.config);
+
+        Ok(())
+    }
+
+    /// Reloads the Task file if it changed on disk since it was last read or
+    /// saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it failed to read the Task file.
+    pub fn reload_if_changed(&mut self) -> anyhow::Result<()> {
+        if modified_time(self.config) != self.modified {
+            self.reload()?;
+        }
+
+        Ok(())
+    }
+
+    fn reload(&mut self) -> anyhow::Result<()> {
+        let selected = self.selected_task_id();
+
+        // Remembered first so a file that can't be read is only retried once
+        // it changes again.
+        self.modified = modified_time(self.config);
+        self.to_do = ToDo::get_to_do(&self.config.to_do_path)?;
+
+        self.project = self.project.min(self.projects().len());
+        self.tag = self.tag.min(self.tags().len());
+        self.task = selected
+            .and_then(|id| {
+                self.visible_tasks().iter().position(|task| task.id == id)
+            })
+            .unwrap_or(0);
+
+        Ok(())
+    }
+}
+
+fn modified_time(config: &Configuration) -> Option<SystemTime> {
+    std::fs::metadata(&config.to_do_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use camino::Utf8PathBuf;
+    use tempfile::TempDir;
+
+    /// A configuration saving Tasks in a directory removed once it's dropped.
+    struct Fixture {
+        config: Configuration,
+        _directory: TempDir,
+    }
+
+    fn fixture() -> Fixture {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let mut config = Configuration::fallback(Language::English);
+        config.to_do_path =
+            Utf8PathBuf::try_from(directory.path().join("todo.ron"))
+                .expect("temporary paths should be UTF-8");
+
+        ToDo::from(vec![
+            Task::create("Write docs")
+                .id(1)
+                .project("Docs")
+                .tag("writing")
+                .build(),
+            Task::create("Fix the parser")
+                .id(2)
+                .project("Code")
+                .tag("bug")
+                .build(),
+            Task::create("Review docs").id(3).project("Docs").build(),
+        ])
+        .save(&config.to_do_path)
+        .expect("Tasks should be saved");
+
+        Fixture {
+            config,
+            _directory: directory,
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+            .expect("the key should be handled");
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for character in text.chars() {
+            press(app, KeyCode::Char(character));
+        }
+    }
+
+    fn visible_ids(app: &App) -> Vec<usize> {
+        app.visible_tasks().iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn switches_panes_and_selects_projects() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+
+        assert_eq!(app.projects(), ["Code", "Docs"]);
+        assert_eq!(app.tags(), ["bug", "writing"]);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.pane, Pane::Projects);
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(app.pane, Pane::Tasks);
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.pane, Pane::Tags);
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.pane, Pane::Tasks);
+
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.task, 1);
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(visible_ids(&app), [2]);
+        assert_eq!(app.task, 0);
+
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Down);
+        }
+        assert_eq!(visible_ids(&app), [1, 3]);
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(visible_ids(&app), Vec::<usize>::new());
+
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(visible_ids(&app), [1]);
+    }
+
+    #[test]
+    fn filters_descriptions_ignoring_case() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "DOCS");
+        assert_eq!(app.mode, Mode::Filter);
+        assert_eq!(visible_ids(&app), [1, 3]);
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.filter, "DOC");
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.filter.is_empty());
+        assert!(!app.should_quit);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn changes_states_and_saves_them() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+        let saved_state = |id| {
+            ToDo::get_to_do(&fixture.config.to_do_path)
+                .expect("Tasks should load")
+                .get_task(id)
+                .map(|task| task.state)
+        };
+
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('2'));
+        assert_eq!(saved_state(2), Some(State::Doing));
+
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(saved_state(2), Some(State::Done));
+
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(saved_state(2), Some(State::ToDo));
+    }
+
+    #[test]
+    fn adds_and_edits_tasks() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "  Proofread  ");
+        press(&mut app, KeyCode::Enter);
+
+        let added = app.to_do.get_task(4).expect("the Task should be added");
+        assert_eq!(
+            (added.description.as_str(), added.project.as_str()),
+            ("Proofread", "Docs")
+        );
+        assert_eq!(
+            app.message.as_deref(),
+            Some(fixture.config.text("added_task"))
+        );
+
+        press(&mut app, KeyCode::Char('e'));
+        assert_eq!(app.mode, Mode::Edit(1));
+
+        press(&mut app, KeyCode::Backspace);
+        type_text(&mut app, "s!");
+        press(&mut app, KeyCode::Enter);
+
+        assert_eq!(
+            ToDo::get_to_do(&fixture.config.to_do_path)
+                .expect("Tasks should load")
+                .get_task(1)
+                .map(|task| task.description.clone()),
+            Some("Write docs!".to_string())
+        );
+    }
+
+    #[test]
+    fn quits_with_ctrl_c_in_any_mode() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "q");
+        assert!(!app.should_quit);
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        ))
+        .expect("the key should be handled");
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn reloading_keeps_the_selected_task() {
+        let fixture = fixture();
+        let mut app = App::new(&fixture.config).expect("Tasks should load");
+
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+
+        let mut changed = app.to_do.clone();
+        changed.tasks.retain(|task| task.id != 1);
+        changed
+            .save(&fixture.config.to_do_path)
+            .expect("Tasks should be saved");
+
+        press(&mut app, KeyCode::Char('r'));
+
+        assert_eq!(visible_ids(&app), [2, 3]);
+        assert_eq!(app.selected_task_id(), Some(3));
+
+        app.modified = None;
+        app.to_do = ToDo::default();
+        app.reload_if_changed().expect("Tasks should load");
+
+        assert_eq!(app.to_do, changed);
+    }
+}
@@ -0,0 +1,52 @@
+mod app;
+mod ui;
+
+use crate::config::Configuration;
+use app::App;
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    DefaultTerminal,
+};
+use std::time::Duration;
+
+/// How long to wait for input before checking the Task file for changes.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// Runs the full-screen terminal interface until the user quits.
+///
+/// # Errors
+///
+/// Returns an error if it failed to draw to the terminal, to read input or to
+/// read the Task file at first. Later failures to read or save it are shown
+/// in the status line instead.
+pub fn run_tui(config: &Configuration) -> anyhow::Result<()> {
+    let mut app = App::new(config)?;
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> anyhow::Result<()> {
+    while !app.should_quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        if event::poll(TICK_RATE)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    if let Err(err) = app.handle_key(key) {
+                        app.error = Some(format!("{err:#}"));
+                    }
+                }
+            }
+        }
+
+        if let Err(err) = app.reload_if_changed() {
+            app.error = Some(format!("{err:#}"));
+        }
+    }
+
+    Ok(())
+}
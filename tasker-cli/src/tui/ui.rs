@@ -0,0 +1,299 @@
+use super::app::{App, Mode, Pane};
+use crate::config::{sgr_codes, Configuration};
+use itertools::Itertools;
+use lib_tasker::todos::{State, Task};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)])
+            .areas(frame.area());
+    let [sidebar, tasks] =
+        Layout::horizontal([Constraint::Percentage(25), Constraint::Fill(1)])
+            .areas(main);
+    let [projects, tags] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Fill(1)])
+            .areas(sidebar);
+
+    let config = app.config;
+    let all = config.text("all");
+
+    draw_sidebar(
+        frame,
+        config,
+        projects,
+        config.text("projects"),
+        std::iter::once(all.to_string()).chain(app.projects()),
+        app.project,
+        app.pane == Pane::Projects,
+    );
+
+    draw_sidebar(
+        frame,
+        config,
+        tags,
+        config.text("tags"),
+        std::iter::once(all.to_string())
+            .chain(app.tags().into_iter().map(|tag| format!("#{tag}"))),
+        app.tag,
+        app.pane == Pane::Tags,
+    );
+
+    draw_tasks(frame, tasks, app);
+    draw_status(frame, status, app);
+
+    match app.mode {
+        Mode::Normal => {}
+        Mode::Filter => {
+            draw_dialog(frame, config.text("filter_title"), &app.input);
+        }
+        Mode::Add => draw_dialog(frame, config.text("new_task"), &app.input),
+        Mode::Edit(_) => {
+            draw_dialog(frame, config.text("edit_task"), &app.input);
+        }
+    }
+}
+
+fn draw_sidebar(
+    frame: &mut Frame,
+    config: &Configuration,
+    area: Rect,
+    title: &str,
+    items: impl Iterator<Item = String>,
+    selected: usize,
+    focused: bool,
+) {
+    let list = List::new(items.map(ListItem::new))
+        .block(
+            Block::bordered()
+                .title(title)
+                .border_style(border(config, focused)),
+        )
+        .highlight_style(highlight(focused));
+
+    frame.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(selected)),
+    );
+}
+
+fn draw_tasks(frame: &mut Frame, area: Rect, app: &App) {
+    let config = app.config;
+    let mut title = config.text("tasks").to_string();
+
+    if !app.filter.is_empty() {
+        title =
+            format!("{title} ({} \"{}\")", config.text("filter"), app.filter);
+    }
+
+    let items = app
+        .visible_tasks()
+        .into_iter()
+        .map(|task| ListItem::new(task_line(task, app)));
+
+    let focused = app.pane == Pane::Tasks;
+    let list = List::new(items)
+        .block(
+            Block::bordered()
+                .title(title)
+                .border_style(border(config, focused)),
+        )
+        .highlight_style(highlight(focused));
+
+    frame.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(app.task)),
+    );
+}
+
+fn task_line<'a>(task: &'a Task, app: &App) -> Line<'a> {
+    let config = app.config;
+    let state = config.text(match task.state {
+        State::ToDo => "state_to_do",
+        State::Doing => "state_doing",
+        State::Waiting => "state_waiting",
+        State::Done => "state_done",
+    });
+
+    let mut spans = vec![
+        Span::styled(format!("{:>3} ", task.id), themed(config.theme.id())),
+        Span::styled(
+            format!("[{state}] "),
+            themed(config.theme.state(task.state)),
+        ),
+        Span::raw(task.description.as_str()),
+    ];
+
+    if app.to_do.is_blocked(task) {
+        spans.push(Span::styled(
+            format!(" [{}]", config.text("blocked")),
+            themed(config.theme.error()),
+        ));
+    }
+
+    if let Some((done, total)) = app.to_do.subtask_progress(task.id) {
+        spans.push(Span::styled(
+            format!(" ({done}/{total})"),
+            themed(config.theme.label()),
+        ));
+    }
+
+    if !task.tags.is_empty() {
+        spans.push(Span::styled(
+            format!(
+                " {}",
+                task.tags.iter().map(|tag| format!("#{tag}")).join(" ")
+            ),
+            themed(config.theme.tag()),
+        ));
+    }
+
+    if !task.annotations.is_empty() {
+        spans.push(Span::styled(" *", themed(config.theme.label())));
+    }
+
+    Line::from(spans)
+}
+
+fn draw_status(frame: &mut Frame, area: Rect, app: &App) {
+    let config = app.config;
+
+    let line = match (&app.error, &app.message) {
+        (Some(error), _) => {
+            Span::styled(error.as_str(), themed(config.theme.error()))
+        }
+        (None, Some(message)) => {
+            Span::styled(message.as_str(), themed(config.theme.success()))
+        }
+        (None, None) => match app.mode {
+            Mode::Normal => config.text("tui_keys").dim(),
+            Mode::Filter | Mode::Add | Mode::Edit(_) => {
+                config.text("tui_dialog_keys").dim()
+            }
+        },
+    };
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_dialog(frame: &mut Frame, title: &str, input: &str) {
+    let area = frame.area();
+    let [_, row, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(3),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    let [_, dialog, _] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Percentage(60),
+        Constraint::Fill(1),
+    ])
+    .areas(row);
+
+    frame.render_widget(Clear, dialog);
+    frame.render_widget(
+        Paragraph::new(input).block(Block::bordered().title(title)),
+        dialog,
+    );
+
+    let cursor = u16::try_from(input.chars().count()).unwrap_or(u16::MAX);
+    frame.set_cursor_position((
+        dialog.x.saturating_add(1).saturating_add(cursor),
+        dialog.y.saturating_add(1),
+    ));
+}
+
+fn border(config: &Configuration, focused: bool) -> Style {
+    if focused {
+        themed(config.theme.label())
+    } else {
+        Style::new()
+    }
+}
+
+const fn highlight(focused: bool) -> Style {
+    if focused {
+        Style::new().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::new().add_modifier(Modifier::BOLD)
+    }
+}
+
+/// Converts a style of the theme, made for printing, into one for the
+/// terminal interface by reading the escape codes it prints with.
+fn themed(style: owo_colors::Style) -> Style {
+    const ANSI: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Gray,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+    ];
+
+    let mut codes = sgr_codes(style).into_iter();
+    let mut themed = Style::new();
+
+    while let Some(code) = codes.next() {
+        let ansi = |offset: u8| ANSI[usize::from(code - offset)];
+
+        themed = match code {
+            1 => themed.add_modifier(Modifier::BOLD),
+            2 => themed.add_modifier(Modifier::DIM),
+            3 => themed.add_modifier(Modifier::ITALIC),
+            4 => themed.add_modifier(Modifier::UNDERLINED),
+            5 => themed.add_modifier(Modifier::SLOW_BLINK),
+            6 => themed.add_modifier(Modifier::RAPID_BLINK),
+            7 => themed.add_modifier(Modifier::REVERSED),
+            8 => themed.add_modifier(Modifier::HIDDEN),
+            9 => themed.add_modifier(Modifier::CROSSED_OUT),
+            30..=37 => themed.fg(ansi(30)),
+            39 => themed.fg(Color::Reset),
+            40..=47 => themed.bg(ansi(40)),
+            49 => themed.bg(Color::Reset),
+            90..=97 => themed.fg(ansi(82)),
+            100..=107 => themed.bg(ansi(92)),
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(Color::Indexed),
+                    Some(2) => match (codes.next(), codes.next(), codes.next())
+                    {
+                        (Some(r), Some(g), Some(b)) => {
+                            Some(Color::Rgb(r, g, b))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                match (code, color) {
+                    (38, Some(color)) => themed.fg(color),
+                    (_, Some(color)) => themed.bg(color),
+                    (_, None) => themed,
+                }
+            }
+            _ => themed,
+        };
+    }
+
+    themed
+}